@@ -0,0 +1,210 @@
+use livesplit_core::{
+    timing::formatter::{Accuracy, Regular, TimeFormatter},
+    TimeSpan, Timer, TimerPhase,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{reset, utils::format_time, SharedState};
+
+#[cfg(target_os = "linux")]
+mod tree;
+#[cfg(target_os = "linux")]
+pub(crate) use tree::AccessibilityTree;
+
+#[derive(Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AccessibilityConfig {
+    // the main window's title describes the timer too. on linux screen readers get the whole
+    // accessibility tree, elsewhere the title is all they can see of the timer
+    pub describe_in_title: bool,
+    // says splits, golds and personal bests out loud
+    pub speak: bool,
+}
+
+/// Notices what happened to the timer since the last frame and puts it into words.
+#[derive(Default)]
+pub(crate) struct Announcer {
+    // the phase and split index from the last frame
+    seen: Option<(TimerPhase, Option<usize>)>,
+    last: Option<String>,
+    // None until it's first needed, Some(None) if it couldn't be started
+    #[cfg(feature = "speech")]
+    speech: Option<Option<tts::Tts>>,
+}
+
+impl Announcer {
+    fn announce(&mut self, text: String, speak: bool) {
+        if speak {
+            self.speak(&text);
+        }
+
+        self.last = Some(text);
+    }
+
+    #[cfg(feature = "speech")]
+    fn speak(&mut self, text: &str) {
+        let speech = self.speech.get_or_insert_with(|| {
+            tts::Tts::default()
+                .map_err(|e| eprintln!("Failed to start text to speech, got error {e}"))
+                .ok()
+        });
+
+        if let Some(speech) = speech {
+            // a split that comes right after another one is more important than the old one
+            speech
+                .speak(text, true)
+                .map(drop)
+                .unwrap_or_else(|e| eprintln!("Failed to speak, got error {e}"));
+        }
+    }
+
+    #[cfg(not(feature = "speech"))]
+    fn speak(&mut self, _text: &str) {}
+}
+
+impl SharedState {
+    /// Called every frame to announce splits, golds, personal bests, pauses and resets, however
+    /// they happened.
+    pub fn check_for_announcements(&mut self) {
+        let timer = self.timer.read();
+        let now = (timer.current_phase(), timer.current_split_index());
+
+        let text = match self.announcer.seen.replace(now) {
+            // nothing happened before the first frame
+            Some(before) if before != now => describe_change(&timer, before),
+            _ => None,
+        };
+
+        drop(timer);
+
+        if let Some(text) = text {
+            self.announcer
+                .announce(text, self.config.accessibility.speak);
+        }
+    }
+
+    /// The last thing that was announced, whether it was spoken or not.
+    pub fn last_announcement(&self) -> Option<&str> {
+        self.announcer.last.as_deref()
+    }
+
+    /// What the timer shows, in a sentence. Times are only down to the second, so it doesn't
+    /// change every frame.
+    pub fn describe_timer(&self) -> String {
+        let timer = self.timer.read();
+        let run = timer.run();
+        let method = timer.current_timing_method();
+
+        let mut text = format!("{} - {}", run.game_name(), run.category_name());
+
+        match timer.current_phase() {
+            TimerPhase::NotRunning => text += ", not running",
+            TimerPhase::Ended => {
+                let time = timer.snapshot().current_time()[method];
+                text += &format!(", finished in {}", format_time(time));
+            }
+            phase => {
+                let time = timer.snapshot().current_time()[method];
+                let index = timer.current_split_index().unwrap_or_default();
+
+                text += &format!(
+                    ", {}, {}",
+                    run.segment(index).name(),
+                    Regular::with_accuracy(Accuracy::Seconds).format(time)
+                );
+
+                if phase == TimerPhase::Paused {
+                    text += ", paused";
+                }
+
+                if let Some(delta) = index.checked_sub(1).and_then(|last| delta(&timer, last)) {
+                    text += &format!(", last split {}", spoken_delta(delta));
+                }
+            }
+        }
+
+        text
+    }
+}
+
+fn describe_change(
+    timer: &Timer,
+    (phase_before, index_before): (TimerPhase, Option<usize>),
+) -> Option<String> {
+    let run = timer.run();
+    let method = timer.current_timing_method();
+    let index = timer.current_split_index();
+
+    let text = match (phase_before, timer.current_phase()) {
+        (_, TimerPhase::NotRunning) => "Reset".to_owned(),
+        (TimerPhase::NotRunning, _) => match index {
+            // started from a later segment in practice mode
+            Some(index) if index > 0 && index < run.len() => {
+                format!("Started at {}", run.segment(index).name())
+            }
+            _ => "Started".to_owned(),
+        },
+        (_, TimerPhase::Ended) => {
+            let time = run.segment(run.len() - 1).split_time()[method];
+            let mut text = format!("Finished in {}", format_time(time));
+
+            if reset::is_new_pb(timer) {
+                text += ", new personal best";
+            }
+
+            text
+        }
+        (TimerPhase::Running, TimerPhase::Paused) => "Paused".to_owned(),
+        (TimerPhase::Paused, TimerPhase::Running) if index == index_before => "Resumed".to_owned(),
+        // the split index changed, e.g. an undo after the end goes back to running
+        _ => match (index_before, index) {
+            (Some(before), Some(index)) if index > before => split_description(timer, index - 1),
+            (Some(_), Some(index)) => format!("Undid the split of {}", run.segment(index).name()),
+            _ => return None,
+        },
+    };
+
+    Some(text)
+}
+
+fn split_description(timer: &Timer, index: usize) -> String {
+    let run = timer.run();
+    let segment = run.segment(index);
+
+    if segment.split_time()[timer.current_timing_method()].is_none() {
+        return format!("Skipped {}", segment.name());
+    }
+
+    let mut text = segment.name().to_owned();
+
+    if let Some(delta) = delta(timer, index) {
+        text += &format!(", {}", spoken_delta(delta));
+    }
+
+    if reset::is_gold(run, index, timer.current_timing_method()) {
+        text += ", gold";
+    }
+
+    text
+}
+
+// how far the split was ahead of or behind the current comparison
+fn delta(timer: &Timer, index: usize) -> Option<TimeSpan> {
+    let method = timer.current_timing_method();
+    let segment = timer.run().segment(index);
+
+    let time = segment.split_time()[method]?;
+    let comparison = segment.comparison_timing_method(timer.current_comparison(), method)?;
+
+    Some(time - comparison)
+}
+
+fn spoken_delta(delta: TimeSpan) -> String {
+    let seconds = delta.total_seconds();
+
+    if seconds < 0. {
+        format!("{:.1} seconds ahead", -seconds)
+    } else {
+        format!("{seconds:.1} seconds behind")
+    }
+}
@@ -0,0 +1,196 @@
+use accesskit::{
+    ActionHandler, ActionRequest, Live, Node, NodeBuilder, NodeClassSet, NodeId, Role, Tree,
+    TreeUpdate,
+};
+use accesskit_unix::Adapter;
+use livesplit_core::{
+    timing::formatter::{Accuracy, Regular, TimeFormatter},
+    TimerPhase,
+};
+
+use crate::{utils::format_time, SharedState};
+
+use super::split_description;
+
+const WINDOW: NodeId = NodeId(0);
+const TITLE: NodeId = NodeId(1);
+const TIME: NodeId = NodeId(2);
+const SPLITS: NodeId = NodeId(3);
+const ANNOUNCEMENT: NodeId = NodeId(4);
+// the segments come after the fixed nodes, in order
+const FIRST_SEGMENT: u64 = 100;
+
+/// What screen readers see of the main window over AT-SPI. The layout itself is only pixels, so
+/// this describes the run, the time, every segment and the last announcement.
+pub(crate) struct AccessibilityTree {
+    // None if there's no accessibility bus to talk to
+    adapter: Option<Adapter>,
+    classes: NodeClassSet,
+    shown: Content,
+}
+
+impl AccessibilityTree {
+    pub(crate) fn new(window_name: &str, focused: bool) -> Self {
+        let mut classes = NodeClassSet::new();
+        let shown = Content::default();
+
+        let mut window = NodeBuilder::new(Role::Window);
+        window.set_name(window_name);
+        window.set_children(vec![TITLE, TIME, SPLITS, ANNOUNCEMENT]);
+
+        let mut nodes = vec![(WINDOW, window.build(&mut classes))];
+        nodes.extend(shown.changes(None, &mut classes));
+
+        let initial = TreeUpdate {
+            nodes,
+            tree: Some(Tree {
+                app_name: Some(window_name.to_owned()),
+                ..Tree::new(WINDOW)
+            }),
+            focus: WINDOW,
+        };
+
+        Self {
+            adapter: Adapter::new(move || initial, focused, Box::new(NoActions)),
+            classes,
+            shown,
+        }
+    }
+
+    /// Called every frame, only what changed since the last one is sent.
+    pub(crate) fn update(&mut self, shared_state: &SharedState) {
+        if let Some(adapter) = &self.adapter {
+            let content = Content::of(shared_state);
+
+            if content != self.shown {
+                adapter.update(TreeUpdate {
+                    nodes: content.changes(Some(&self.shown), &mut self.classes),
+                    tree: None,
+                    focus: WINDOW,
+                });
+                self.shown = content;
+            }
+        }
+    }
+
+    pub(crate) fn set_focused(&self, focused: bool) {
+        if let Some(adapter) = &self.adapter {
+            adapter.update_window_focus_state(focused);
+        }
+    }
+}
+
+// everything is read only, the timer is controlled with the hotkeys
+struct NoActions;
+
+impl ActionHandler for NoActions {
+    fn do_action(&mut self, _request: ActionRequest) {}
+}
+
+#[derive(Default, PartialEq)]
+struct Content {
+    title: String,
+    // down to the second, so it doesn't change every frame
+    time: String,
+    segments: Vec<String>,
+    announcement: String,
+}
+
+impl Content {
+    fn of(shared_state: &SharedState) -> Self {
+        let timer = shared_state.timer.read();
+        let run = timer.run();
+        let method = timer.current_timing_method();
+        let phase = timer.current_phase();
+
+        let mut time = Regular::with_accuracy(Accuracy::Seconds)
+            .format(timer.snapshot().current_time()[method])
+            .to_string();
+
+        match phase {
+            TimerPhase::NotRunning => time += ", not running",
+            TimerPhase::Paused => time += ", paused",
+            TimerPhase::Ended => time += ", finished",
+            TimerPhase::Running => {}
+        }
+
+        // the index is past the last segment once the run ended
+        let current = timer.current_split_index();
+        let segments = run
+            .segments()
+            .iter()
+            .enumerate()
+            .map(|(index, segment)| match current {
+                Some(current) if index < current => split_description(&timer, index),
+                Some(current) if index == current => {
+                    format!("{}, current segment", segment.name())
+                }
+                _ => {
+                    let comparison =
+                        segment.comparison_timing_method(timer.current_comparison(), method);
+                    format!("{}, {}", segment.name(), format_time(comparison))
+                }
+            })
+            .collect();
+
+        Self {
+            title: format!("{} - {}", run.game_name(), run.category_name()),
+            time,
+            segments,
+            announcement: shared_state
+                .last_announcement()
+                .unwrap_or_default()
+                .to_owned(),
+        }
+    }
+
+    // the nodes that are different from before, or all of them if nothing was shown yet
+    fn changes(&self, before: Option<&Content>, classes: &mut NodeClassSet) -> Vec<(NodeId, Node)> {
+        let mut nodes = Vec::new();
+
+        if before.is_none_or(|before| before.title != self.title) {
+            let mut title = NodeBuilder::new(Role::Heading);
+            title.set_name(self.title.as_str());
+            nodes.push((TITLE, title.build(classes)));
+        }
+
+        if before.is_none_or(|before| before.time != self.time) {
+            let mut time = NodeBuilder::new(Role::Timer);
+            time.set_name("Time");
+            time.set_value(self.time.as_str());
+            nodes.push((TIME, time.build(classes)));
+        }
+
+        let segments_before = before.map(|before| before.segments.as_slice());
+
+        // the list only changes when segments were added or removed
+        if segments_before.map(<[String]>::len) != Some(self.segments.len()) {
+            let mut splits = NodeBuilder::new(Role::List);
+            splits.set_name("Splits");
+            splits.set_children((0..self.segments.len()).map(segment_id).collect::<Vec<_>>());
+            nodes.push((SPLITS, splits.build(classes)));
+        }
+
+        for (index, segment) in self.segments.iter().enumerate() {
+            if segments_before.and_then(|before| before.get(index)) != Some(segment) {
+                let mut item = NodeBuilder::new(Role::ListItem);
+                item.set_name(segment.as_str());
+                nodes.push((segment_id(index), item.build(classes)));
+            }
+        }
+
+        // screen readers read out a polite live region whenever its name changes
+        if before.is_none_or(|before| before.announcement != self.announcement) {
+            let mut announcement = NodeBuilder::new(Role::Status);
+            announcement.set_live(Live::Polite);
+            announcement.set_name(self.announcement.as_str());
+            nodes.push((ANNOUNCEMENT, announcement.build(classes)));
+        }
+
+        nodes
+    }
+}
+
+fn segment_id(index: usize) -> NodeId {
+    NodeId(FIRST_SEGMENT + index as u64)
+}
@@ -0,0 +1,351 @@
+use std::{
+    fmt, fs,
+    path::{Path, PathBuf},
+    sync::{
+        mpsc::{self, RecvTimeoutError, Sender},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+};
+
+use livesplit_auto_splitting::{
+    time, InterruptHandle, Runtime, SettingsStore, Timer as ScriptTimer, TimerState,
+};
+use livesplit_core::{SharedTimer, Timer, TimerPhase};
+
+use crate::{configuration::Configuration, Hotkey, SharedState};
+
+/// Runs the WebAssembly auto splitter that belongs to the current splits. The runtime can only
+/// drive a timer directly, which would skip the reset policy and practice mode, so it gets a timer
+/// of its own and `SharedState::update_auto_splitter` carries over what it did.
+pub struct AutoSplitter {
+    timer: SharedTimer,
+    // the phase and split index of the auto splitter's timer after the last update
+    seen: (TimerPhase, Option<usize>),
+    script: Option<PathBuf>,
+    // why the script couldn't be loaded, or why it stopped. its thread sets it too
+    error: Arc<Mutex<Option<String>>>,
+    running: Option<RunningScript>,
+}
+
+// the thread the script runs on, it stops when `stop` is dropped
+struct RunningScript {
+    stop: Sender<()>,
+    interrupt: InterruptHandle,
+    thread: JoinHandle<()>,
+}
+
+impl AutoSplitter {
+    pub fn new(timer: &Timer) -> Self {
+        let timer = Timer::new(timer.run().clone()).unwrap().into_shared();
+
+        Self {
+            timer,
+            seen: (TimerPhase::NotRunning, None),
+            script: None,
+            error: Default::default(),
+            running: None,
+        }
+    }
+
+    pub fn script(&self) -> Option<&Path> {
+        self.script.as_deref()
+    }
+
+    /// Why the auto splitter couldn't be loaded, or what went wrong while running it.
+    pub fn error(&self) -> Option<String> {
+        self.error.lock().unwrap().clone()
+    }
+
+    pub fn load(&mut self, script: PathBuf) -> Result<(), String> {
+        self.unload();
+
+        let running = fs::read(&script)
+            .map_err(|e| e.to_string())
+            .and_then(|module| {
+                RunningScript::start(
+                    module,
+                    ScriptTimerHandle(self.timer.clone()),
+                    self.error.clone(),
+                )
+            });
+
+        match running {
+            Ok(running) => {
+                self.running = Some(running);
+                self.script = Some(script);
+                Ok(())
+            }
+            Err(e) => {
+                let e = format!("Failed to load auto splitter, got error {e}");
+                *self.error.lock().unwrap() = Some(e.clone());
+                Err(e)
+            }
+        }
+    }
+
+    pub fn unload(&mut self) {
+        self.script = None;
+
+        if let Some(running) = self.running.take() {
+            running.stop();
+        }
+
+        // the thread is gone, it can't report anything about the old script anymore
+        *self.error.lock().unwrap() = None;
+    }
+
+    /// Loads the auto splitter remembered for the configured splits, or unloads the current one
+    /// if there is none.
+    pub fn load_for_splits(&mut self, config: &Configuration) {
+        let script = config
+            .profile()
+            .splits_path
+            .as_ref()
+            .and_then(|splits_path| config.auto_splitters.get(splits_path));
+
+        match script {
+            Some(script) => {
+                if self.script() != Some(script.as_path()) {
+                    self.load(script.clone()).ok();
+                }
+            }
+            None => self.unload(),
+        }
+    }
+}
+
+impl Drop for AutoSplitter {
+    fn drop(&mut self) {
+        self.unload();
+    }
+}
+
+impl RunningScript {
+    // the runtime can't be sent to another thread everywhere, so it's made on the script's own.
+    // returns once the script is loaded
+    fn start(
+        module: Vec<u8>,
+        timer: ScriptTimerHandle,
+        error: Arc<Mutex<Option<String>>>,
+    ) -> Result<Self, String> {
+        let (stop, stopped) = mpsc::channel();
+        let (loaded, load_result) = mpsc::channel();
+
+        let thread = thread::spawn(move || {
+            let mut runtime = match Runtime::new(&module, timer, SettingsStore::new()) {
+                Ok(runtime) => runtime,
+                Err(e) => {
+                    loaded.send(Err(e.to_string())).ok();
+                    return;
+                }
+            };
+
+            loaded.send(Ok(runtime.interrupt_handle())).ok();
+
+            loop {
+                match runtime.update() {
+                    Ok(tick_rate) => match stopped.recv_timeout(tick_rate) {
+                        Err(RecvTimeoutError::Timeout) => {}
+                        _ => return,
+                    },
+                    Err(e) => {
+                        // it was interrupted because it's being unloaded
+                        if stopped.try_recv() != Err(mpsc::TryRecvError::Empty) {
+                            return;
+                        }
+
+                        eprintln!("Auto splitter stopped, got error {e:?}");
+                        *error.lock().unwrap() =
+                            Some(format!("The auto splitter stopped, got error {e}"));
+                        return;
+                    }
+                }
+            }
+        });
+
+        let interrupt = match load_result.recv() {
+            Ok(result) => result,
+            Err(_) => Err("the auto splitter's thread panicked".to_owned()),
+        };
+
+        match interrupt {
+            Ok(interrupt) => Ok(Self {
+                stop,
+                interrupt,
+                thread,
+            }),
+            Err(e) => {
+                thread.join().ok();
+                Err(e)
+            }
+        }
+    }
+
+    fn stop(self) {
+        drop(self.stop);
+        // a script stuck in a loop wouldn't notice otherwise
+        self.interrupt.interrupt();
+        self.thread.join().ok();
+    }
+}
+
+// the runtime needs a timer it can own, this is the auto splitter's own one
+struct ScriptTimerHandle(SharedTimer);
+
+impl ScriptTimer for ScriptTimerHandle {
+    fn state(&self) -> TimerState {
+        match self.0.read().current_phase() {
+            TimerPhase::NotRunning => TimerState::NotRunning,
+            TimerPhase::Running => TimerState::Running,
+            TimerPhase::Paused => TimerState::Paused,
+            TimerPhase::Ended => TimerState::Ended,
+        }
+    }
+
+    fn start(&mut self) {
+        self.0.write().start();
+    }
+
+    fn split(&mut self) {
+        self.0.write().split();
+    }
+
+    fn reset(&mut self) {
+        self.0.write().reset(true);
+    }
+
+    fn set_game_time(&mut self, time: time::Duration) {
+        self.0.write().set_game_time(time.into());
+    }
+
+    fn pause_game_time(&mut self) {
+        self.0.write().pause_game_time();
+    }
+
+    fn resume_game_time(&mut self) {
+        self.0.write().resume_game_time();
+    }
+
+    fn set_variable(&mut self, key: &str, value: &str) {
+        self.0.write().set_custom_variable(key, value);
+    }
+
+    fn log(&mut self, message: fmt::Arguments<'_>) {
+        eprintln!("Auto splitter: {message}");
+    }
+}
+
+impl SharedState {
+    /// The timer the auto splitter drives, see `AutoSplitter`.
+    pub fn auto_splitter_timer(&self) -> &SharedTimer {
+        &self.auto_splitter.timer
+    }
+
+    /// Called every frame to do what the auto splitter did to its timer since the last frame,
+    /// through the shared state like a hotkey. Then its timer is brought in line with the real
+    /// one, e.g. after the user split.
+    pub fn update_auto_splitter(&mut self) {
+        let script_timer = self.auto_splitter.timer.clone();
+
+        let (phase, index, game_time) = {
+            let timer = script_timer.read();
+            let game_time = timer.is_game_time_initialized().then(|| {
+                (
+                    timer.snapshot().current_time().game_time,
+                    timer.is_game_time_paused(),
+                )
+            });
+
+            (
+                timer.current_phase(),
+                timer.current_split_index(),
+                game_time,
+            )
+        };
+        let (seen_phase, seen_index) = self.auto_splitter.seen;
+
+        if phase == TimerPhase::NotRunning && seen_phase != TimerPhase::NotRunning {
+            self.run_hotkey(Hotkey::Reset);
+        } else {
+            if seen_phase == TimerPhase::NotRunning && phase != TimerPhase::NotRunning {
+                // the user might have started it already
+                if self.timer.read().current_phase() == TimerPhase::NotRunning {
+                    self.run_hotkey(Hotkey::Split);
+                }
+            }
+
+            let splits = index
+                .unwrap_or_default()
+                .saturating_sub(seen_index.unwrap_or_default());
+
+            for _ in 0..splits {
+                self.run_hotkey(Hotkey::Split);
+            }
+
+            if let Some((game_time, paused)) = game_time {
+                let mut timer = self.timer.write();
+
+                if !timer.is_game_time_initialized() {
+                    timer.initialize_game_time();
+                }
+
+                if let Some(game_time) = game_time {
+                    timer.set_game_time(game_time);
+                }
+
+                if paused && !timer.is_game_time_paused() {
+                    timer.pause_game_time();
+                } else if !paused && timer.is_game_time_paused() {
+                    timer.resume_game_time();
+                }
+            }
+        }
+
+        let timer = self.timer.read();
+        let mut script_timer = script_timer.write();
+
+        match (timer.current_phase(), script_timer.current_phase()) {
+            // e.g. another profile or edited splits, the split indices have to match
+            (TimerPhase::NotRunning, TimerPhase::NotRunning)
+                if script_timer.run().len() != timer.run().len() =>
+            {
+                script_timer.replace_run(timer.run().clone(), false).ok();
+            }
+            (TimerPhase::NotRunning, TimerPhase::NotRunning) => {}
+            (TimerPhase::NotRunning, _) => script_timer.reset(false),
+            (_, TimerPhase::NotRunning) => script_timer.start(),
+            _ => {}
+        }
+
+        // stops if the timer won't move, e.g. while it's paused
+        while script_timer.current_split_index() < timer.current_split_index() {
+            let before = script_timer.current_split_index();
+
+            // the last split can't be skipped
+            if before.is_some_and(|index| index + 1 >= script_timer.run().len()) {
+                script_timer.split();
+            } else {
+                script_timer.skip_split();
+            }
+
+            if script_timer.current_split_index() == before {
+                break;
+            }
+        }
+
+        while script_timer.current_split_index() > timer.current_split_index() {
+            let before = script_timer.current_split_index();
+            script_timer.undo_split();
+
+            if script_timer.current_split_index() == before {
+                break;
+            }
+        }
+
+        self.auto_splitter.seen = (
+            script_timer.current_phase(),
+            script_timer.current_split_index(),
+        );
+    }
+}
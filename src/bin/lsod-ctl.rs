@@ -0,0 +1,68 @@
+//! Controls a running timer from the command line, e.g. `lsod-ctl split`.
+
+#[cfg(unix)]
+#[path = "../ipc/path.rs"]
+mod path;
+
+const USAGE: &str = "usage: lsod-ctl <command>
+
+commands:
+    start                              start the timer
+    split                              split
+    undo                               undo the last split
+    skip                               skip the current split
+    pause                              pause or resume the timer
+    reset                              reset the timer
+    comparison <next|previous|<name>>  switch the comparison
+    time                               print the current time";
+
+#[cfg(unix)]
+fn main() {
+    use std::{
+        env,
+        io::{BufRead, BufReader, Write},
+        os::unix::net::UnixStream,
+        process,
+    };
+
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    if args.is_empty() || args[0] == "--help" || args[0] == "-h" {
+        println!("{USAGE}");
+        return;
+    }
+
+    let path = path::socket_path();
+
+    let mut stream = UnixStream::connect(&path).unwrap_or_else(|e| {
+        eprintln!(
+            "Could not connect to the timer at {}, got error {e}",
+            path.display()
+        );
+        process::exit(1);
+    });
+
+    writeln!(stream, "{}", args.join(" ")).expect("Could not send command");
+
+    let mut response = String::new();
+    BufReader::new(stream)
+        .read_line(&mut response)
+        .expect("Could not read response");
+
+    let response = response.trim_end();
+
+    if let Some(error) = response.strip_prefix("error: ") {
+        eprintln!("{error}");
+        process::exit(1);
+    }
+
+    if response != "ok" {
+        println!("{response}");
+    }
+}
+
+#[cfg(not(unix))]
+fn main() {
+    eprintln!("lsod-ctl only works on unix systems\n\n{USAGE}");
+    std::process::exit(1);
+}
@@ -1,14 +1,29 @@
+use anyhow::{anyhow, bail, Context};
+use glutin::dpi::{LogicalSize, PhysicalSize};
 use livesplit_core::{HotkeyConfig, Layout, Run, Segment};
+use rfd::{MessageDialog, MessageLevel};
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 use std::{
+    collections::HashMap,
+    env,
     error::Error,
     fmt::Display,
-    fs::{File, OpenOptions},
-    io::{BufReader, BufWriter},
-    path::PathBuf,
+    fs::{self, File, OpenOptions},
+    io::{BufReader, BufWriter, ErrorKind},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
 };
 
-use crate::utils::{load_layout_from_file, load_splits_from_file};
+use crate::{
+    accessibility::AccessibilityConfig,
+    gui::appearance::{Appearance, Theme, MAX_SCALE, MIN_SCALE},
+    hotkey::ExtraHotkeyConfig,
+    reset::ResetPolicy,
+    server::ServerConfig,
+    utils::{load_layout_from_file, load_splits_from_file},
+    websocket::WebSocketConfig,
+};
 
 #[derive(Debug)]
 struct NoConfigDirError;
@@ -21,74 +36,416 @@ impl Display for NoConfigDirError {
 
 impl Error for NoConfigDirError {}
 
-#[derive(Serialize, Deserialize)]
-pub struct Configuration {
-    #[serde(default)]
+#[derive(Debug)]
+struct NewerVersionError(u64);
+
+impl Display for NewerVersionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "it was written by a newer version of the timer (version {})",
+            self.0
+        )
+    }
+}
+
+impl Error for NewerVersionError {}
+
+// overrides where the configuration is
+const CONFIG_PATH_VAR: &str = "LSOD_CONFIG";
+// a configuration with this name next to the executable makes the timer portable
+const PORTABLE_FILE_NAME: &str = "lsod_config.json";
+
+// each one turns a config of the version it's at into the next version, so a new format needs a
+// new migration at the end
+const MIGRATIONS: &[fn(&mut Map<String, Value>)] =
+    &[unversioned_to_1, move_into_profile, mark_physical_sizes];
+
+const CURRENT_VERSION: u32 = MIGRATIONS.len() as u32;
+
+// configs from before there were versions already look like version 1
+fn unversioned_to_1(_config: &mut Map<String, Value>) {}
+
+// version 1 had a single set of splits, layout, hotkeys and size, which becomes the first profile
+fn move_into_profile(config: &mut Map<String, Value>) {
+    let mut profile = Map::new();
+    profile.insert("name".to_owned(), DEFAULT_PROFILE_NAME.into());
+
+    for key in [
+        "splits_path",
+        "layout_path",
+        "hotkey_config",
+        "extra_hotkey_config",
+        "size",
+    ] {
+        if let Some(value) = config.remove(key) {
+            profile.insert(key.to_owned(), value);
+        }
+    }
+
+    config.insert("profiles".to_owned(), vec![Value::Object(profile)].into());
+    config.insert("current_profile".to_owned(), 0.into());
+}
+
+// sizes were in physical pixels up to version 2. the scale factor is only known once there's an
+// event loop, so they're converted then
+fn mark_physical_sizes(config: &mut Map<String, Value>) {
+    let profiles = config.get_mut("profiles").and_then(Value::as_array_mut);
+
+    for profile in profiles.into_iter().flatten() {
+        if let Some(profile) = profile.as_object_mut() {
+            if profile.contains_key("size") {
+                profile.insert("size_is_physical".to_owned(), true.into());
+            }
+        }
+    }
+}
+
+const DEFAULT_PROFILE_NAME: &str = "Default";
+
+pub const MIN_ZOOM: f64 = 0.25;
+pub const MAX_ZOOM: f64 = 4.;
+
+/// The splits, layout, hotkeys and window size that belong together, e.g. one per category.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Profile {
+    pub name: String,
     pub splits_path: Option<PathBuf>,
-    #[serde(default)]
     pub layout_path: Option<PathBuf>,
-    #[serde(default)]
     pub hotkey_config: HotkeyConfig,
-
-    #[serde(default = "default_size")]
+    pub extra_hotkey_config: ExtraHotkeyConfig,
+    // in logical pixels, so the timer is as big on every monitor
     pub size: (u32, u32),
+    // set on sizes from older configs until they're converted to logical pixels
+    pub size_is_physical: bool,
+    // how much bigger than its size the timer is drawn
+    pub zoom: f64,
 }
 
-fn default_size() -> (u32, u32) {
-    (320, 240)
-}
-impl Default for Configuration {
+impl Default for Profile {
     fn default() -> Self {
         Self {
+            name: DEFAULT_PROFILE_NAME.to_owned(),
             splits_path: None,
             layout_path: None,
             hotkey_config: Default::default(),
-            size: default_size(),
+            extra_hotkey_config: Default::default(),
+            size: (320, 240),
+            size_is_physical: false,
+            zoom: 1.,
+        }
+    }
+}
+
+impl Profile {
+    /// The size of the timer window with the zoom, in logical pixels.
+    pub fn window_size(&self) -> LogicalSize<f64> {
+        LogicalSize::new(
+            self.size.0 as f64 * self.zoom,
+            self.size.1 as f64 * self.zoom,
+        )
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Configuration {
+    // missing in configs from before there were versions
+    #[serde(default)]
+    pub version: u32,
+    // never empty
+    #[serde(default = "default_profiles")]
+    pub profiles: Vec<Profile>,
+    #[serde(default)]
+    pub current_profile: usize,
+    // auto splitter to use for each splits file
+    #[serde(default)]
+    pub auto_splitters: HashMap<PathBuf, PathBuf>,
+    #[serde(default)]
+    pub server: ServerConfig,
+    #[serde(default)]
+    pub websocket: WebSocketConfig,
+    // write practice attempts to practice_log.txt
+    #[serde(default)]
+    pub practice_log: bool,
+    #[serde(default)]
+    pub reset_policy: ResetPolicy,
+    #[serde(default)]
+    pub accessibility: AccessibilityConfig,
+    // of the configuration window
+    #[serde(default)]
+    pub appearance: Appearance,
+    // the file is from a newer version of the timer, saving over it would lose what this version
+    // doesn't know about
+    #[serde(skip)]
+    read_only: bool,
+}
+
+fn default_profiles() -> Vec<Profile> {
+    vec![Profile::default()]
+}
+
+impl Default for Configuration {
+    fn default() -> Self {
+        Self {
+            version: CURRENT_VERSION,
+            profiles: default_profiles(),
+            current_profile: 0,
+            auto_splitters: Default::default(),
+            server: Default::default(),
+            websocket: Default::default(),
+            practice_log: false,
+            reset_policy: Default::default(),
+            accessibility: Default::default(),
+            appearance: Default::default(),
+            read_only: false,
         }
     }
 }
 
 impl Configuration {
-    fn get() -> Option<Self> {
-        let file = File::open(Configuration::get_path()?).ok()?;
+    pub fn profile(&self) -> &Profile {
+        &self.profiles[self.current_profile]
+    }
 
-        serde_json::de::from_reader(BufReader::new(file))
-            .map_err(|e| println!("Failed to load config, got error: {e}"))
-            .ok()
-            .map(|mut config: Configuration| {
-                config.validate();
-                config
-            })
+    pub fn profile_mut(&mut self) -> &mut Profile {
+        &mut self.profiles[self.current_profile]
+    }
+
+    /// Turns the sizes older versions saved in physical pixels into logical ones. The monitor they
+    /// were measured on isn't known, so they're all converted with the same scale factor.
+    pub fn convert_physical_sizes(&mut self, scale_factor: f64) {
+        for profile in &mut self.profiles {
+            if profile.size_is_physical {
+                let size = PhysicalSize::new(profile.size.0, profile.size.1)
+                    .to_logical::<f64>(scale_factor);
+
+                profile.size = (
+                    (size.width.round() as u32).max(1),
+                    (size.height.round() as u32).max(1),
+                );
+                profile.size_is_physical = false;
+            }
+        }
     }
 
+    /// Reads the configuration file and brings it up to the current version. Returns `None` if
+    /// there is no configuration yet.
+    pub fn load() -> anyhow::Result<Option<Self>> {
+        let path = match Configuration::get_path() {
+            Some(path) => path,
+            None => return Ok(None),
+        };
+
+        Configuration::move_from_old_location(&path)?;
+
+        let file = match File::open(&path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut config: Map<String, Value> = serde_json::from_reader(BufReader::new(file))?;
+
+        let version = match config.get("version") {
+            Some(version) => version
+                .as_u64()
+                .ok_or_else(|| anyhow!("the version has to be a number, got {version}"))?,
+            None => 0,
+        };
+
+        if version > CURRENT_VERSION.into() {
+            return Err(NewerVersionError(version).into());
+        }
+
+        // the old file still works with the version of the timer that wrote it
+        let backed_up = version < CURRENT_VERSION.into()
+            && Configuration::back_up()
+                .map_err(|e| {
+                    eprintln!("Failed to back up configuration before updating it, got error {e}")
+                })
+                .is_ok();
+
+        for migration in &MIGRATIONS[version as usize..] {
+            migration(&mut config);
+        }
+
+        config.insert("version".to_owned(), CURRENT_VERSION.into());
+
+        let mut config: Configuration = serde_json::from_value(Value::Object(config))?;
+        config.validate();
+
+        // saved at the current version right away, so the same old file isn't backed up again
+        // every time it's loaded
+        if backed_up {
+            config.save().unwrap_or_else(|e| {
+                eprintln!("Failed to save the updated configuration, got error {e}")
+            });
+        }
+
+        Ok(Some(config))
+    }
+
+    // copies the configuration file next to itself, so we don't overwrite one we couldn't read or
+    // that's from an older version. the backups are named after when they were made, so none of
+    // them are overwritten
+    fn back_up() -> anyhow::Result<PathBuf> {
+        let path = Configuration::get_path().ok_or(NoConfigDirError)?;
+
+        let time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let mut backup = path.with_extension(format!("json.{time}.bak"));
+
+        // several backups in the same second
+        let mut count = 1;
+        while backup.exists() {
+            count += 1;
+            backup = path.with_extension(format!("json.{time}-{count}.bak"));
+        }
+
+        fs::copy(&path, &backup)?;
+
+        Ok(backup)
+    }
+
+    /// Where the configuration is read from and saved to. That's the file in `LSOD_CONFIG` if it's
+    /// set, then `lsod_config.json` next to the executable if there is one (portable mode), and
+    /// otherwise the timer's directory in the user's config directory.
     pub fn get_path() -> Option<PathBuf> {
+        if let Some(path) = env::var_os(CONFIG_PATH_VAR) {
+            return Some(path.into());
+        }
+
+        let portable_path = env::current_exe()
+            .ok()
+            .map(|exe| exe.with_file_name(PORTABLE_FILE_NAME));
+
+        match portable_path {
+            Some(path) if path.exists() => Some(path),
+            _ => Configuration::default_path(),
+        }
+    }
+
+    fn default_path() -> Option<PathBuf> {
+        let mut location = dirs::config_dir()?;
+        location.push("lsod-egui");
+        location.push("config.json");
+        Some(location)
+    }
+
+    // the configuration used to be directly in the config directory
+    fn old_default_path() -> Option<PathBuf> {
         let mut location = dirs::config_dir()?;
         location.push("lsod_config.json");
         Some(location)
     }
 
+    fn move_from_old_location(path: &Path) -> anyhow::Result<()> {
+        if path.exists() || Configuration::default_path().as_deref() != Some(path) {
+            return Ok(());
+        }
+
+        let old_path = match Configuration::old_default_path() {
+            Some(old_path) if old_path.exists() => old_path,
+            _ => return Ok(()),
+        };
+
+        let move_config = || {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+
+            fs::rename(&old_path, path)
+        };
+
+        move_config().with_context(|| {
+            format!(
+                "could not move it from {} to {}",
+                old_path.display(),
+                path.display()
+            )
+        })
+    }
+
     pub fn save(&self) -> anyhow::Result<()> {
+        if self.read_only {
+            bail!("the configuration is from a newer version of the timer, it's kept as it is");
+        }
+
+        let path = Configuration::get_path().ok_or(NoConfigDirError)?;
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
         let file = OpenOptions::new()
             .write(true)
             .truncate(true)
             .create(true)
-            .open(Configuration::get_path().ok_or(NoConfigDirError)?)?;
+            .open(path)?;
 
         serde_json::ser::to_writer(BufWriter::new(file), self).map_err(Into::into)
     }
 
+    /// Loads the configuration, falling back to the defaults if there is none. If it can't be
+    /// read, it's backed up and the user is told about it.
     pub fn get_or_default() -> Self {
-        Configuration::get().unwrap_or_default()
+        let (config, error) = Configuration::load_or_default();
+
+        if let Some(description) = error {
+            MessageDialog::new()
+                .set_title("Failed to Load Configuration")
+                .set_level(MessageLevel::Warning)
+                .set_description(&description)
+                .show();
+        }
+
+        config
+    }
+
+    /// Like `get_or_default`, but returns what the user should be told instead of telling them.
+    /// The defaults used in place of a configuration from a newer version aren't saved.
+    pub fn load_or_default() -> (Self, Option<String>) {
+        let e = match Configuration::load() {
+            Ok(config) => return (config.unwrap_or_default(), None),
+            Err(e) => e,
+        };
+
+        let config = Configuration {
+            read_only: e.is::<NewerVersionError>(),
+            ..Default::default()
+        };
+
+        let mut description = match Configuration::back_up() {
+            Ok(backup) => format!(
+                "Could not read the configuration, got error {e:#}\n\nThe default settings are used \
+                 instead. The old configuration was backed up to {}",
+                backup.display()
+            ),
+            Err(backup_error) => format!(
+                "Could not read the configuration, got error {e:#}\n\nThe default settings are used \
+                 instead. The old configuration could not be backed up, got error {backup_error}"
+            ),
+        };
+
+        if config.read_only {
+            description += "\n\nSettings changed now won't be saved, so the newer version of the \
+                            timer can still use its configuration.";
+        }
+
+        (config, Some(description))
     }
 
     pub fn layout_or_default(&self) -> Layout {
-        match &self.layout_path {
+        match &self.profile().layout_path {
             Some(path) => load_layout_from_file(path).unwrap_or_else(|_| Layout::default_layout()),
             None => Layout::default_layout(),
         }
     }
     pub fn run_or_default(&self) -> Run {
-        match &self.splits_path {
+        match &self.profile().splits_path {
             Some(path) => load_splits_from_file(path).unwrap_or_else(|e| {
                 eprintln!("Failed to load splits, got error {e}");
                 Self::default_run()
@@ -108,12 +465,36 @@ impl Configuration {
     }
 
     fn validate(&mut self) {
-        if self.size.0 == 0 {
-            self.size.0 = 1;
+        if self.profiles.is_empty() {
+            self.profiles = default_profiles();
         }
 
-        if self.size.1 == 0 {
-            self.size.1 = 1;
+        if self.current_profile >= self.profiles.len() {
+            self.current_profile = 0;
+        }
+
+        // e.g. a config from a system that can find out its theme
+        if !Theme::AVAILABLE.contains(&self.appearance.theme) {
+            self.appearance.theme = Theme::default();
+        }
+
+        // only the scales the settings offer, others could make the window unusable
+        if !(MIN_SCALE..=MAX_SCALE).contains(&self.appearance.scale) {
+            self.appearance.scale = 1.;
+        }
+
+        for profile in &mut self.profiles {
+            if profile.size.0 == 0 {
+                profile.size.0 = 1;
+            }
+
+            if profile.size.1 == 0 {
+                profile.size.1 = 1;
+            }
+
+            if !(MIN_ZOOM..=MAX_ZOOM).contains(&profile.zoom) {
+                profile.zoom = 1.;
+            }
         }
     }
 }
@@ -1,4 +1,289 @@
-fn spawn_context_menu() {
-    
+use egui_glow::egui_winit::egui::{self, Button, Checkbox, ScrollArea, Ui};
+use glutin::{
+    dpi::{LogicalSize, PhysicalPosition},
+    event::{KeyboardInput, VirtualKeyCode, WindowEvent},
+    event_loop::EventLoopWindowTarget,
+    window::{WindowBuilder, WindowId},
+};
+use livesplit_core::{TimeSpan, TimerPhase};
+
+use crate::{
+    gui::{profiles::show_switch_error, EguiWindow},
+    hotkey::Hotkey,
+    practice::show_start_error,
+    window::ApplicationWindow,
+    SharedState, UserEvent,
+};
+
+enum Page {
+    Actions,
+    SetGameTime(String),
+    StartFromSegment,
+    Profiles,
+}
+
+/// The menu that pops up when right clicking the timer.
+pub struct ContextMenu {
+    window: EguiWindow,
+    page: Page,
+}
+
+impl ApplicationWindow for ContextMenu {
+    fn window_event(&mut self, event: WindowEvent, shared_state: &mut SharedState) {
+        match event {
+            WindowEvent::KeyboardInput {
+                input:
+                    KeyboardInput {
+                        virtual_keycode: Some(VirtualKeyCode::Escape),
+                        ..
+                    },
+                ..
+            }
+            | WindowEvent::Focused(false) => self.close(shared_state),
+            _ => {}
+        }
+
+        self.window.on_event(&event);
+    }
+
+    fn redraw(&mut self, shared_state: &mut SharedState) {
+        let page = &mut self.page;
+        let mut close = false;
+
+        self.window.redraw(|ctx| {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                close = match page {
+                    Page::Actions => actions(ui, page, shared_state),
+                    Page::SetGameTime(text) => set_game_time(ui, text, shared_state),
+                    Page::StartFromSegment => start_from_segment(ui, shared_state),
+                    Page::Profiles => profiles(ui, shared_state),
+                };
+            });
+        });
+
+        if close {
+            self.close(shared_state);
+        }
+    }
+
+    fn id(&self) -> WindowId {
+        self.window.id()
+    }
+
+    fn request_redraw(&mut self) {
+        self.window.window().request_redraw();
+    }
+}
+
+impl ContextMenu {
+    pub fn new(
+        event_loop: &EventLoopWindowTarget<UserEvent>,
+        position: PhysicalPosition<i32>,
+    ) -> Self {
+        let wb = WindowBuilder::new()
+            .with_title("LiveSplit One")
+            .with_decorations(false)
+            .with_resizable(false)
+            .with_always_on_top(true)
+            .with_position(position)
+            .with_inner_size(LogicalSize::new(200, 495));
+
+        let window = EguiWindow::new(wb, event_loop, [0., 0., 0.]);
+        window.window().focus_window();
+
+        Self {
+            window,
+            page: Page::Actions,
+        }
+    }
+
+    pub fn id(&self) -> WindowId {
+        self.window.id()
+    }
+
+    fn close(&self, shared_state: &SharedState) {
+        shared_state
+            .send_event
+            .send_event(UserEvent::DestroyWindow(self.id()))
+            .ok();
+    }
 }
 
+// returns true if the menu should be closed
+fn actions(ui: &mut Ui, page: &mut Page, shared_state: &mut SharedState) -> bool {
+    let mut action = |ui: &mut Ui, hotkey: Hotkey| {
+        let clicked = ui.button(hotkey.get_name()).clicked();
+
+        if clicked {
+            shared_state.run_hotkey(hotkey);
+        }
+
+        clicked
+    };
+
+    let mut close = false;
+
+    close |= action(ui, Hotkey::Split);
+    close |= action(ui, Hotkey::Reset);
+    close |= action(ui, Hotkey::ResetAndDiscard);
+    close |= action(ui, Hotkey::Undo);
+    close |= action(ui, Hotkey::Skip);
+    close |= action(ui, Hotkey::Pause);
+    close |= action(ui, Hotkey::UndoAllPauses);
+
+    ui.separator();
+
+    close |= action(ui, Hotkey::PreviousComparison);
+    close |= action(ui, Hotkey::NextComparison);
+    close |= action(ui, Hotkey::ToggleTimingMethod);
+
+    ui.separator();
+
+    close |= action(ui, Hotkey::InitializeGameTime);
+    close |= action(ui, Hotkey::PauseGameTime);
+    close |= action(ui, Hotkey::ResumeGameTime);
+
+    if ui.button("Set Game Time...").clicked() {
+        *page = Page::SetGameTime(String::new());
+    }
+
+    ui.separator();
+
+    let mut practice_mode = shared_state.practice_mode;
+    let between_attempts = shared_state.timer.read().current_phase() == TimerPhase::NotRunning;
+
+    if ui
+        .add_enabled(between_attempts, Button::new("Start From Segment..."))
+        .clicked()
+    {
+        *page = Page::StartFromSegment;
+    }
+
+    if ui
+        .add_enabled(
+            between_attempts,
+            Checkbox::new(&mut practice_mode, "Practice Mode"),
+        )
+        .changed()
+    {
+        shared_state.toggle_practice_mode().ok();
+        close = true;
+    }
+
+    if ui.button("Profiles...").clicked() {
+        *page = Page::Profiles;
+    }
+
+    if ui.button("Settings").clicked() {
+        if !shared_state.has_configuration_window {
+            shared_state.has_configuration_window = true;
+            shared_state
+                .send_event
+                .send_event(UserEvent::SpawnConfigurationWindow)
+                .ok();
+        }
+
+        close = true;
+    }
+
+    close
+}
+
+fn set_game_time(ui: &mut Ui, text: &mut String, shared_state: &mut SharedState) -> bool {
+    ui.label("Game Time");
+
+    let text_edit = ui.text_edit_singleline(text);
+    text_edit.request_focus();
+
+    let game_time = text.parse::<TimeSpan>();
+
+    if game_time.is_err() && !text.is_empty() {
+        ui.label("That isn't a valid time");
+    }
+
+    let submitted = text_edit.lost_focus() && ui.input().key_pressed(egui::Key::Enter);
+
+    let set = ui
+        .add_enabled(game_time.is_ok(), Button::new("Set"))
+        .clicked()
+        || submitted;
+
+    if let (true, Ok(game_time)) = (set, game_time) {
+        let mut timer = shared_state.timer.write();
+
+        // setting the game time does nothing until game time has been initialized
+        if !timer.is_game_time_initialized() {
+            timer.initialize_game_time();
+        }
+
+        timer.set_game_time(game_time);
+
+        return true;
+    }
+
+    ui.button("Cancel").clicked()
+}
+
+fn start_from_segment(ui: &mut Ui, shared_state: &mut SharedState) -> bool {
+    ui.label("Start From Segment");
+
+    let mut start_from = None;
+
+    ScrollArea::vertical().max_height(340.).show(ui, |ui| {
+        for (index, segment) in shared_state
+            .timer
+            .read()
+            .run()
+            .segments()
+            .iter()
+            .enumerate()
+        {
+            if ui.button(segment.name()).clicked() {
+                start_from = Some(index);
+            }
+        }
+    });
+
+    if let Some(index) = start_from {
+        if let Err(e) = shared_state.start_from_segment(index) {
+            show_start_error(&e);
+        }
+
+        return true;
+    }
+
+    ui.button("Cancel").clicked()
+}
+
+fn profiles(ui: &mut Ui, shared_state: &mut SharedState) -> bool {
+    ui.label("Profiles");
+
+    // the configuration window might be editing the current profile's splits
+    if shared_state.has_configuration_window {
+        ui.label("Close the settings to switch profiles");
+    }
+
+    let current = shared_state.config.current_profile;
+    let mut switch_to = None;
+
+    ui.add_enabled_ui(!shared_state.has_configuration_window, |ui| {
+        for (index, profile) in shared_state.config.profiles.iter().enumerate() {
+            if ui
+                .selectable_label(index == current, &profile.name)
+                .clicked()
+            {
+                switch_to = Some(index);
+            }
+        }
+    });
+
+    if let Some(index) = switch_to {
+        if let Err(e) = shared_state.switch_profile(index) {
+            show_switch_error(&e);
+        }
+
+        return true;
+    }
+
+    ui.button("Cancel").clicked()
+}
@@ -0,0 +1,54 @@
+use std::{mem::take, sync::Arc};
+
+use glutin::event_loop::{EventLoopClosed, EventLoopProxy};
+use livesplit_core::parking_lot::Mutex;
+
+use crate::UserEvent;
+
+/// Where `UserEvent`s are sent. That's the event loop, unless we are running without one.
+pub trait EventSink: Send {
+    fn send_event(&self, event: UserEvent) -> Result<(), EventLoopClosed<UserEvent>>;
+    fn boxed_clone(&self) -> EventSender;
+}
+
+pub type EventSender = Box<dyn EventSink>;
+
+impl Clone for EventSender {
+    fn clone(&self) -> Self {
+        self.boxed_clone()
+    }
+}
+
+impl EventSink for EventLoopProxy<UserEvent> {
+    fn send_event(&self, event: UserEvent) -> Result<(), EventLoopClosed<UserEvent>> {
+        EventLoopProxy::send_event(self, event)
+    }
+
+    fn boxed_clone(&self) -> EventSender {
+        Box::new(self.clone())
+    }
+}
+
+/// Keeps the events around instead of handling them, so tests can check what was sent.
+#[derive(Clone, Default)]
+pub struct EventQueue {
+    events: Arc<Mutex<Vec<UserEvent>>>,
+}
+
+impl EventQueue {
+    /// Removes and returns everything sent so far.
+    pub fn take(&self) -> Vec<UserEvent> {
+        take(&mut *self.events.lock())
+    }
+}
+
+impl EventSink for EventQueue {
+    fn send_event(&self, event: UserEvent) -> Result<(), EventLoopClosed<UserEvent>> {
+        self.events.lock().push(event);
+        Ok(())
+    }
+
+    fn boxed_clone(&self) -> EventSender {
+        Box::new(self.clone())
+    }
+}
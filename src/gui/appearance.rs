@@ -0,0 +1,163 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use ab_glyph::FontRef;
+use egui_glow::egui_winit::egui::{Rgba, Visuals};
+use glutin::window::Window;
+use rfd::{MessageDialog, MessageLevel};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Theme {
+    // only where the system's theme can be found out
+    #[cfg_attr(any(windows, target_os = "linux"), default)]
+    System,
+    Light,
+    #[cfg_attr(not(any(windows, target_os = "linux")), default)]
+    Dark,
+}
+
+impl Theme {
+    /// The themes the settings offer.
+    #[cfg(any(windows, target_os = "linux"))]
+    pub const AVAILABLE: &'static [Theme] = &[Theme::System, Theme::Light, Theme::Dark];
+    #[cfg(not(any(windows, target_os = "linux")))]
+    pub const AVAILABLE: &'static [Theme] = &[Theme::Light, Theme::Dark];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Theme::System => "System",
+            Theme::Light => "Light",
+            Theme::Dark => "Dark",
+        }
+    }
+}
+
+/// How the configuration window looks.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Appearance {
+    pub theme: Theme,
+    // on top of the monitor's scale factor
+    pub scale: f32,
+    // a ttf or otf file, the bundled arial if there's none
+    pub font: Option<PathBuf>,
+}
+
+impl Default for Appearance {
+    fn default() -> Self {
+        Self {
+            theme: Theme::default(),
+            scale: 1.,
+            font: None,
+        }
+    }
+}
+
+pub const MIN_SCALE: f32 = 0.75;
+pub const MAX_SCALE: f32 = 2.5;
+pub const SCALES: [f32; 7] = [MIN_SCALE, 1., 1.25, 1.5, 1.75, 2., MAX_SCALE];
+
+impl Appearance {
+    pub(crate) fn visuals(&self, window: &Window) -> Visuals {
+        let dark = match self.theme {
+            Theme::System => system_is_dark(window),
+            Theme::Light => false,
+            Theme::Dark => true,
+        };
+
+        if dark {
+            Visuals::dark()
+        } else {
+            Visuals::light()
+        }
+    }
+
+    /// The font file's contents, if there is one and it can be used. Arial is used otherwise.
+    pub(crate) fn font_data(&self) -> Option<Vec<u8>> {
+        let path = self.font.as_ref()?;
+
+        // e.g. the file was replaced since it was chosen
+        load_font(path).map_err(|e| show_font_error(path, e)).ok()
+    }
+}
+
+/// Reads a ttf or otf file and makes sure egui can use it, it panics on fonts it can't parse.
+pub fn load_font(path: &Path) -> anyhow::Result<Vec<u8>> {
+    let data = fs::read(path)?;
+
+    // the same check egui does before it panics
+    FontRef::try_from_slice(&data)?;
+
+    Ok(data)
+}
+
+pub(crate) fn show_font_error(path: &Path, e: anyhow::Error) {
+    MessageDialog::new()
+        .set_title("Failed to Load Font")
+        .set_level(MessageLevel::Error)
+        .set_description(
+            format!(
+                "Could not use {} as the font, got error {e}",
+                path.display()
+            )
+            .as_str(),
+        )
+        .show();
+}
+
+#[cfg(windows)]
+fn system_is_dark(window: &Window) -> bool {
+    use glutin::{platform::windows::WindowExtWindows, window::Theme};
+
+    window.theme() == Theme::Dark
+}
+
+// winit doesn't know the theme here, the desktop's preference is read from the settings portal.
+// without one there is no preference, which means light
+#[cfg(target_os = "linux")]
+fn system_is_dark(_window: &Window) -> bool {
+    // 1 is dark, 2 is light and 0 is no preference
+    matches!(portal_color_scheme(), Ok(1))
+}
+
+#[cfg(target_os = "linux")]
+fn portal_color_scheme() -> zbus::Result<u32> {
+    use zbus::{
+        blocking::Connection,
+        zvariant::{OwnedValue, Value},
+    };
+
+    let reply = Connection::session()?.call_method(
+        Some("org.freedesktop.portal.Desktop"),
+        "/org/freedesktop/portal/desktop",
+        Some("org.freedesktop.portal.Settings"),
+        "Read",
+        &("org.freedesktop.appearance", "color-scheme"),
+    )?;
+
+    // the setting comes in a variant of its own
+    let reply: OwnedValue = reply.body()?;
+    let mut value: &Value = &reply;
+    while let Value::Value(inner) = value {
+        value = inner;
+    }
+
+    match value {
+        Value::U32(scheme) => Ok(*scheme),
+        _ => Err(zbus::Error::InvalidReply),
+    }
+}
+
+// a config from a system that can find out its theme, it's validated to the dark theme
+#[cfg(not(any(windows, target_os = "linux")))]
+fn system_is_dark(_window: &Window) -> bool {
+    true
+}
+
+pub(crate) fn clear_color(visuals: &Visuals) -> [f32; 3] {
+    let color = Rgba::from(visuals.window_fill());
+    [color.r(), color.g(), color.b()]
+}
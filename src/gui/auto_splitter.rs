@@ -0,0 +1,60 @@
+use egui_glow::egui_winit::egui::{Color32, Ui};
+use rfd::{FileDialog, MessageDialog, MessageLevel};
+
+use crate::SharedState;
+
+pub(super) fn auto_splitter(ui: &mut Ui, shared_state: &mut SharedState) {
+    ui.heading("Auto Splitter");
+
+    let splits_path = match shared_state.config.profile().splits_path.clone() {
+        Some(splits_path) => splits_path,
+        None => {
+            ui.label(
+                "Auto splitters are remembered per splits file. Load or save your splits first.",
+            );
+            return;
+        }
+    };
+
+    ui.label(format!("Splits: {}", splits_path.display()));
+
+    match shared_state.auto_splitter.script() {
+        Some(script) => ui.label(format!("Running {}", script.display())),
+        None => ui.label("No auto splitter loaded"),
+    };
+
+    if let Some(error) = shared_state.auto_splitter.error() {
+        ui.colored_label(Color32::RED, error);
+    }
+
+    ui.horizontal(|ui| {
+        if ui.button("Load Auto Splitter").clicked() {
+            let path = FileDialog::new()
+                .add_filter("WebAssembly Auto Splitter", &["wasm"])
+                .pick_file();
+
+            if let Some(path) = path {
+                match shared_state.auto_splitter.load(path.clone()) {
+                    Ok(()) => {
+                        shared_state
+                            .config
+                            .auto_splitters
+                            .insert(splits_path.clone(), path);
+                    }
+                    Err(e) => {
+                        MessageDialog::new()
+                            .set_title("Failed to load auto splitter")
+                            .set_level(MessageLevel::Error)
+                            .set_description(&e)
+                            .show();
+                    }
+                }
+            }
+        }
+
+        if ui.button("Unload").clicked() {
+            shared_state.auto_splitter.unload();
+            shared_state.config.auto_splitters.remove(&splits_path);
+        }
+    });
+}
@@ -0,0 +1,177 @@
+use egui_glow::egui_winit::egui::{Button, CollapsingHeader, Grid, ScrollArea, Ui};
+use livesplit_core::{Run, TimeSpan, TimingMethod};
+
+use rfd::{MessageButtons, MessageDialog};
+
+use crate::utils::format_time;
+
+use super::SplitsState;
+
+pub(super) fn attempt_history(ui: &mut Ui, state: &mut SplitsState) {
+    CollapsingHeader::new("Attempt History").show(ui, |ui| {
+        let mut attempt_to_remove = None;
+        let method = state.editor.selected_timing_method();
+
+        ScrollArea::vertical()
+            .id_source("attempt history")
+            .max_height(0.5 * ui.available_height())
+            .show(ui, |ui| {
+                Grid::new("attempts").striped(true).show(ui, |ui| {
+                    ui.heading("#");
+                    ui.heading("Started");
+                    ui.heading("Duration");
+                    ui.heading("Pause Time");
+                    ui.heading("Final Time");
+                    ui.heading("");
+                    ui.end_row();
+
+                    for attempt in state.editor.run().attempt_history().iter().rev() {
+                        let index = attempt.index();
+
+                        if ui
+                            .selectable_label(
+                                state.selected_attempt == Some(index),
+                                index.to_string(),
+                            )
+                            .clicked()
+                        {
+                            state.selected_attempt = Some(index);
+                        }
+
+                        ui.label(match attempt.started() {
+                            Some(started) => started.time.to_string(),
+                            None => "-".to_owned(),
+                        });
+                        ui.label(format_time(attempt.duration()));
+                        ui.label(format_time(attempt.pause_time()));
+                        ui.label(match attempt.time()[method] {
+                            Some(time) => format_time(Some(time)),
+                            None => "Reset".to_owned(),
+                        });
+
+                        if ui
+                            .add_enabled(!state.live, Button::new("Delete").small())
+                            .clicked()
+                        {
+                            attempt_to_remove = Some(index);
+                        }
+
+                        ui.end_row();
+                    }
+                });
+            });
+
+        // there's no getting it back
+        let remove = attempt_to_remove.filter(|index| {
+            MessageDialog::new()
+                .set_buttons(MessageButtons::YesNo)
+                .set_title("Delete Attempt?")
+                .set_description(&format!(
+                    "Would you really like to delete attempt #{index}? Its segment times are \
+                     deleted too, and golds are recalculated without it."
+                ))
+                .show()
+        });
+
+        if let Some(index) = remove {
+            state.remove_attempt(index);
+        }
+
+        if let Some(index) = state.selected_attempt {
+            ui.separator();
+            attempt_breakdown(ui, state.editor.run(), index, method);
+        }
+    });
+}
+
+fn attempt_breakdown(ui: &mut Ui, run: &Run, attempt_index: i32, method: TimingMethod) {
+    ui.label(format!("Attempt #{attempt_index}"));
+
+    Grid::new("attempt breakdown").striped(true).show(ui, |ui| {
+        ui.heading("Segment Name");
+        ui.heading("Split Time");
+        ui.heading("Segment Time");
+        ui.end_row();
+
+        let mut split_time = Some(TimeSpan::zero());
+
+        for segment in run.segments() {
+            let segment_time = match segment.segment_history().get(attempt_index) {
+                Some(time) => time[method],
+                // the attempt never got this far
+                None => break,
+            };
+
+            split_time = match (split_time, segment_time) {
+                (Some(split_time), Some(segment_time)) => Some(split_time + segment_time),
+                _ => None,
+            };
+
+            ui.label(segment.name());
+            ui.label(format_time(split_time));
+            ui.label(match segment_time {
+                Some(time) => format_time(Some(time)),
+                None => "Skipped".to_owned(),
+            });
+            ui.end_row();
+        }
+    });
+}
+
+/// Removes an attempt and all of its segment history from the run. Any best segment times that
+/// came from the attempt are recalculated from the history that's left.
+pub fn remove_attempt(run: &mut Run, attempt_index: i32) {
+    let attempts: Vec<_> = run
+        .attempt_history()
+        .iter()
+        .filter(|attempt| attempt.index() != attempt_index)
+        .cloned()
+        .collect();
+
+    let mut histories: Vec<_> = run
+        .segments()
+        .iter()
+        .map(|segment| segment.segment_history().clone())
+        .collect();
+
+    // clearing the history is the only way to get rid of an attempt, so we put back everything
+    // we want to keep afterwards
+    run.clear_history();
+
+    for attempt in attempts {
+        run.add_attempt_with_index(
+            attempt.time(),
+            attempt.index(),
+            attempt.started(),
+            attempt.ended(),
+            attempt.pause_time(),
+        );
+    }
+
+    for (segment_index, history) in histories.iter_mut().enumerate() {
+        let removed = history.get(attempt_index);
+        history.remove(attempt_index);
+
+        let segment = run.segment_mut(segment_index);
+        *segment.segment_history_mut() = history.clone();
+
+        let removed = match removed {
+            Some(removed) => removed,
+            None => continue,
+        };
+
+        let mut best_segment_time = segment.best_segment_time();
+
+        for method in [TimingMethod::RealTime, TimingMethod::GameTime] {
+            if removed[method].is_some() && removed[method] == best_segment_time[method] {
+                best_segment_time[method] =
+                    history.iter().filter_map(|(_, time)| time[method]).min();
+            }
+        }
+
+        segment.set_best_segment_time(best_segment_time);
+    }
+
+    run.fix_splits();
+    run.mark_as_modified();
+}
@@ -0,0 +1,94 @@
+use egui_glow::egui_winit::egui::{Ui, Window};
+use livesplit_core::run::editor::Editor;
+
+use super::{SegmentLocal, SplitsState};
+
+/// Progress of the "Clean Sum of Best" wizard. The cleaner borrows the editor so it can't be
+/// kept around between frames, instead we recreate it and skip the entries the user wanted to
+/// keep. Those are told apart by their description, which names the segments and the attempt,
+/// since deleting an entry changes which ones come after it.
+pub(super) struct CleaningState {
+    kept: Vec<String>,
+    current: Option<String>,
+}
+
+impl CleaningState {
+    pub fn new(editor: &mut Editor) -> Self {
+        let mut state = Self {
+            kept: Vec::new(),
+            current: None,
+        };
+
+        state.update(editor, false);
+
+        state
+    }
+
+    // finds the next potential clean up, removing the current one first if requested
+    fn update(&mut self, editor: &mut Editor, remove_current: bool) {
+        let mut cleaner = editor.clean_sum_of_best();
+
+        self.current = loop {
+            let clean_up = match cleaner.next_potential_clean_up() {
+                Some(clean_up) => clean_up,
+                None => break None,
+            };
+
+            let description = clean_up.to_string();
+
+            if remove_current && self.current.as_ref() == Some(&description) {
+                let clean_up = clean_up.into();
+                cleaner.apply(clean_up);
+            } else if !self.kept.contains(&description) {
+                break Some(description);
+            }
+        };
+    }
+}
+
+pub(super) fn clean_sum_of_best(ui: &mut Ui, state: &mut SplitsState) {
+    // taken out while the window is shown, the buttons need the rest of the state
+    let mut cleaning = match state.cleaning.take() {
+        Some(cleaning) => cleaning,
+        None => return,
+    };
+
+    let mut close = false;
+
+    Window::new("Clean Sum of Best")
+        .collapsible(false)
+        .resizable(false)
+        .show(ui.ctx(), |ui| match cleaning.current.clone() {
+            Some(current) => {
+                ui.label(current.as_str());
+
+                ui.horizontal(|ui| {
+                    if ui.button("Delete").clicked() {
+                        cleaning.update(&mut state.editor, true);
+                        state.segments = SegmentLocal::gen_vec(&mut state.editor);
+                        state.change_segments();
+                    }
+
+                    if ui.button("Keep").clicked() {
+                        cleaning.kept.push(current);
+                        cleaning.update(&mut state.editor, false);
+                    }
+
+                    if ui.button("Cancel").clicked() {
+                        close = true;
+                    }
+                });
+            }
+            None => {
+                ui.label("There is nothing (left) to clean up.");
+
+                if ui.button("Close").clicked() {
+                    close = true;
+                }
+            }
+        });
+
+    if !close {
+        state.cleaning = Some(cleaning);
+    }
+}
@@ -1,7 +1,9 @@
 use egui_glow::egui_winit::egui::{Grid, Ui};
-use livesplit_core::run::editor;
+use livesplit_core::{run::editor, TimingMethod};
 
-use super::SplitsState;
+use crate::{run_edits::RunEdit, utils::timing_method_name};
+
+use super::{pick_icon, SegmentLocal, SplitsState};
 
 pub(super) fn split_metadata(
     ui: &mut Ui,
@@ -15,8 +17,7 @@ pub(super) fn split_metadata(
                 ui.label("Game Name");
 
                 if ui.text_edit_singleline(&mut editor_state.game).changed() {
-                    state.editor.set_game_name(&editor_state.game);
-                    state.dirty = true;
+                    state.edit(RunEdit::GameName(editor_state.game.clone()));
                 }
             });
 
@@ -27,8 +28,7 @@ pub(super) fn split_metadata(
                     .text_edit_singleline(&mut editor_state.category)
                     .changed()
                 {
-                    state.editor.set_category_name(&editor_state.category);
-                    state.dirty = true;
+                    state.edit(RunEdit::CategoryName(editor_state.category.clone()));
                 }
             });
 
@@ -40,8 +40,7 @@ pub(super) fn split_metadata(
                 let text = ui.text_edit_singleline(&mut editor_state.offset);
 
                 if text.changed() {
-                    state.editor.parse_and_set_offset(&editor_state.offset).ok();
-                    state.dirty = true;
+                    state.edit(RunEdit::Offset(editor_state.offset.clone()));
                 }
             });
 
@@ -52,17 +51,46 @@ pub(super) fn split_metadata(
                 // TODO is there a better way to do this
                 let text = ui.text_edit_singleline(&mut state.attempts_string);
                 if text.changed() {
-                    state
-                        .editor
-                        .parse_and_set_attempt_count(&state.attempts_string)
-                        .ok();
-
-                    state.dirty = true;
+                    state.edit(RunEdit::AttemptCount(state.attempts_string.clone()));
                 }
 
                 if text.lost_focus() {
                     state.sync_attempts_string();
                 }
-            })
+            });
+
+            ui.end_row();
+
+            ui.vertical(|ui| {
+                ui.label("Timing Method");
+
+                ui.horizontal(|ui| {
+                    for method in [TimingMethod::RealTime, TimingMethod::GameTime] {
+                        let selected = state.editor.selected_timing_method() == method;
+
+                        if ui.radio(selected, timing_method_name(method)).clicked() && !selected {
+                            state.editor.select_timing_method(method);
+                            // the times we have cached are in the old timing method
+                            state.segments = SegmentLocal::gen_vec(&mut state.editor);
+                        }
+                    }
+                });
+            });
+
+            ui.vertical(|ui| {
+                ui.label("Game Icon");
+
+                ui.horizontal(|ui| {
+                    if ui.button("Set").clicked() {
+                        if let Some(icon) = pick_icon() {
+                            state.edit(RunEdit::GameIcon(Some(icon)));
+                        }
+                    }
+
+                    if ui.button("Remove").clicked() {
+                        state.edit(RunEdit::GameIcon(None));
+                    }
+                });
+            });
         });
 }
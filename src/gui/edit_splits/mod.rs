@@ -1,56 +1,94 @@
-use std::{fmt::Write, fs::File, io::BufWriter};
+use std::{
+    fmt::Write,
+    fs::{self, File},
+    io::BufWriter,
+    mem::take,
+};
 
-use egui_glow::egui_winit::egui::Ui;
+use egui_glow::egui_winit::egui::{Button, Key, Modifiers, Ui};
 use livesplit_core::{
-    run::editor::{Editor, SegmentState},
+    run::editor::{Editor, SegmentState, SelectionState},
     run::saver::livesplit::save_run,
     timing::formatter::timer::{Fraction, Time},
-    Run,
+    Run, TimerPhase,
 };
 
 use lazy_static::lazy_static;
-use rfd::{MessageButtons, MessageDialog};
+use rfd::{FileDialog, MessageButtons, MessageDialog, MessageLevel};
+
+use crate::{practice::show_start_error, run_edits::RunEdit, utils::save_splits, SharedState};
 
-use crate::SharedState;
+use self::cleaning::CleaningState;
 
 lazy_static! {
     static ref TIME_FORMATTER: Time = Time::default();
     static ref FRACTION_FORMATTER: Fraction = Fraction::default();
 }
 
+mod attempts;
+pub use attempts::remove_attempt;
+mod cleaning;
 mod meta;
 mod segments;
 
+// the contents of the image the user picks for an icon
+fn pick_icon() -> Option<Vec<u8>> {
+    let path = FileDialog::new()
+        .add_filter("Image", &["png", "jpg", "jpeg", "gif", "bmp", "ico"])
+        .pick_file()?;
+
+    fs::read(&path)
+        .map_err(|e| eprintln!("Failed to load icon {}, got error {e}", path.display()))
+        .ok()
+}
+
 fn split_editing_buttons(ui: &mut Ui, state: &mut SplitsState) {
-    ui.vertical(|ui| {
+    let active = state
+        .editor
+        .state()
+        .segments
+        .iter()
+        .position(|segment| matches!(segment.selected, SelectionState::Active));
+
+    // icons are cosmetic, they can change during an attempt
+    if let Some(active) = active {
+        if ui.button("Set Icon").clicked() {
+            if let Some(icon) = pick_icon() {
+                state.edit(RunEdit::SegmentIcon(active, Some(icon)));
+            }
+        }
+
+        if ui.button("Remove Icon").clicked() {
+            state.edit(RunEdit::SegmentIcon(active, None));
+        }
+    }
+
+    // the running attempt needs its segments to stay where they are
+    ui.add_enabled_ui(!state.live, |ui| {
         if ui.button("Insert Above").clicked() {
             state.editor.insert_segment_above();
             state.segments = SegmentLocal::gen_vec(&mut state.editor);
-            state.dirty = true;
+            state.change_segments();
         };
 
         if ui.button("Insert Below").clicked() {
-            state.editor.insert_segment_below();
-            state.segments = SegmentLocal::gen_vec(&mut state.editor);
-            state.dirty = true;
+            state.insert_segment_below();
         };
 
         if ui.button("Remove Segment").clicked() {
-            state.editor.remove_segments();
-            state.segments = SegmentLocal::gen_vec(&mut state.editor);
-            state.dirty = true;
+            state.remove_segments();
         };
 
         if ui.button("Move Up").clicked() {
             state.editor.move_segments_up();
             state.segments = SegmentLocal::gen_vec(&mut state.editor);
-            state.dirty = true;
+            state.change_segments();
         };
 
         if ui.button("Move Down").clicked() {
             state.editor.move_segments_down();
             state.segments = SegmentLocal::gen_vec(&mut state.editor);
-            state.dirty = true;
+            state.change_segments();
         };
 
         if ui.button("Clear History").clicked() {
@@ -59,7 +97,11 @@ fn split_editing_buttons(ui: &mut Ui, state: &mut SplitsState) {
 
             state.sync_attempts_string();
 
-            state.dirty = true;
+            state.change_segments();
+        };
+
+        if ui.button("Clean Sum of Best").clicked() {
+            state.cleaning = Some(CleaningState::new(&mut state.editor));
         };
 
         if ui.button("Clear Times").clicked() {
@@ -68,12 +110,48 @@ fn split_editing_buttons(ui: &mut Ui, state: &mut SplitsState) {
 
             state.sync_attempts_string();
 
-            state.dirty = true;
+            state.change_segments();
         };
     });
 }
 
+// ctrl+s saves, delete and insert remove and add segments when no text field takes the keys
+fn shortcuts(ui: &mut Ui, shared_state: &mut SharedState, state: &mut SplitsState) {
+    if ui.input_mut().consume_key(Modifiers::COMMAND, Key::S) && state.dirty {
+        state.save_run(shared_state);
+    }
+
+    if state.live || ui.ctx().wants_keyboard_input() {
+        return;
+    }
+
+    if ui.input().key_pressed(Key::Delete) {
+        state.remove_segments();
+    }
+
+    if ui.input().key_pressed(Key::Insert) {
+        state.insert_segment_below();
+    }
+}
+
 pub(super) fn edit_splits(ui: &mut Ui, shared_state: &mut SharedState, state: &mut SplitsState) {
+    let live = shared_state.timer.read().current_phase() != TimerPhase::NotRunning;
+
+    // the attempt is over, show the splits with it in them
+    if state.live && !live && !state.dirty {
+        *state = SplitsState::new(shared_state.timer.read().run().clone());
+    }
+
+    state.live = live;
+
+    shortcuts(ui, shared_state, state);
+
+    if live {
+        ui.label(
+            "The timer is running. Names and icons are saved and shown right away, times are changed after the reset.",
+        );
+    }
+
     // we edit this local copy and then update the actual editor with any changes egui makes
     let mut editor_state = state.editor.state();
 
@@ -84,16 +162,37 @@ pub(super) fn edit_splits(ui: &mut Ui, shared_state: &mut SharedState, state: &m
         segments::segments(ui, state);
     });
 
+    attempts::attempt_history(ui, state);
+
+    ui.add_enabled_ui(!live, |ui| cleaning::clean_sum_of_best(ui, state));
+
     ui.horizontal(|ui| {
         if ui.button("Save").clicked() && state.dirty {
             state.save_run(shared_state);
-
-            state.dirty = false;
         }
 
         if ui.button("Discard Changes").clicked() {
             *state = SplitsState::new(shared_state.timer.read().run().clone());
         }
+
+        // the timer has to be running the splits that are shown
+        let can_start = !state.dirty && !live;
+
+        if ui
+            .add_enabled(can_start, Button::new("Start From Selected Segment"))
+            .clicked()
+        {
+            let active = editor_state
+                .segments
+                .iter()
+                .position(|segment| matches!(segment.selected, SelectionState::Active));
+
+            if let Some(index) = active {
+                if let Err(e) = shared_state.start_from_segment(index) {
+                    show_start_error(&e);
+                }
+            }
+        }
     });
 }
 
@@ -133,6 +232,14 @@ pub(super) struct SplitsState {
     dirty: bool,
     segments: Vec<SegmentLocal>,
     grid_width: f32,
+    selected_attempt: Option<i32>,
+    cleaning: Option<CleaningState>,
+    // the edits since the last save, they can be made to the timer's run even during an attempt
+    queued: Vec<RunEdit>,
+    // edits that aren't in `queued`, these need the whole run replaced
+    changed_segments: bool,
+    // whether the timer is running
+    live: bool,
 }
 
 impl SplitsState {
@@ -145,6 +252,11 @@ impl SplitsState {
             editor,
             dirty: false,
             grid_width: 0.0,
+            selected_attempt: None,
+            cleaning: None,
+            queued: Vec::new(),
+            changed_segments: false,
+            live: false,
         }
     }
 
@@ -167,6 +279,29 @@ impl SplitsState {
         true
     }
 
+    fn edit(&mut self, edit: RunEdit) {
+        edit.apply(&mut self.editor);
+        self.queued.push(edit);
+        self.dirty = true;
+    }
+
+    fn insert_segment_below(&mut self) {
+        self.editor.insert_segment_below();
+        self.segments = SegmentLocal::gen_vec(&mut self.editor);
+        self.change_segments();
+    }
+
+    fn remove_segments(&mut self) {
+        self.editor.remove_segments();
+        self.segments = SegmentLocal::gen_vec(&mut self.editor);
+        self.change_segments();
+    }
+
+    fn change_segments(&mut self) {
+        self.changed_segments = true;
+        self.dirty = true;
+    }
+
     pub(crate) fn run(&self) -> &Run {
         self.editor.run()
     }
@@ -176,7 +311,52 @@ impl SplitsState {
         write!(self.attempts_string, "{}", self.editor.attempt_count()).ok();
     }
 
+    fn remove_attempt(&mut self, attempt_index: i32) {
+        // the editor has no way to remove single attempts, so we edit a copy of the run instead
+        let mut run = self.editor.run().clone();
+        attempts::remove_attempt(&mut run, attempt_index);
+
+        self.editor = Editor::new(run).expect("Could not eddit run");
+        self.segments = SegmentLocal::gen_vec(&mut self.editor);
+        self.sync_attempts_string();
+
+        if self.selected_attempt == Some(attempt_index) {
+            self.selected_attempt = None;
+        }
+
+        self.change_segments();
+    }
+
     fn save_run(&mut self, shared_state: &mut SharedState) {
+        if !self.changed_segments {
+            // made to the timer's run, so nothing that happened since the editor opened is lost
+            shared_state.queue_edits(take(&mut self.queued));
+            shared_state.apply_queued_edits();
+
+            let saved = if self.live {
+                // the times are only saved with the attempt
+                shared_state.save_cosmetic_edits()
+            } else {
+                save_splits(shared_state.timer.read().run())
+            };
+
+            saved.unwrap_or_else(|e| eprintln!("Failed to save splits, got error {e}"));
+
+            self.dirty = false;
+            return;
+        }
+
+        if self.live {
+            MessageDialog::new()
+                .set_title("Can't Save Splits")
+                .set_level(MessageLevel::Error)
+                .set_description(
+                    "Segments and attempts can't change while the timer is running, save them after the reset.",
+                )
+                .show();
+            return;
+        }
+
         if let Some(path) = self.editor.run().path() {
             if let Ok(file) = File::create(path) {
                 save_run(self.editor.run(), BufWriter::new(file)).ok();
@@ -189,5 +369,9 @@ impl SplitsState {
 
         let mut timer = shared_state.timer.write();
         timer.replace_run(self.editor.run().clone(), false).ok();
+
+        self.queued.clear();
+        self.changed_segments = false;
+        self.dirty = false;
     }
 }
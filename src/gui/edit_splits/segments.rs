@@ -1,12 +1,23 @@
 use std::mem::take;
 
-use egui_glow::egui_winit::egui::{Color32, Grid, Rect, ScrollArea, Ui, Vec2};
+use egui_glow::egui_winit::egui::{Color32, Grid, Id, Key, Rect, ScrollArea, Ui, Vec2};
+use livesplit_core::run::editor::SelectionState;
+
+use crate::run_edits::RunEdit;
 
 use super::SplitsState;
 
 pub(super) fn segments(ui: &mut Ui, state: &mut SplitsState) {
     // this active regeneration is necessary because we may have deleted splits this frame
     let mut editor_state = state.editor.state();
+    let method = state.editor.selected_timing_method();
+    let active = editor_state
+        .segments
+        .iter()
+        .position(|segment| matches!(segment.selected, SelectionState::Active));
+
+    // the text fields of every row, for moving between them with the arrow keys
+    let mut rows = Vec::new();
 
     ScrollArea::both()
         .max_height(0.67 * ui.available_height())
@@ -34,6 +45,9 @@ pub(super) fn segments(ui: &mut Ui, state: &mut SplitsState) {
 
                     // empyty label for padding
 
+                    // made after the row, while nothing else is borrowed
+                    let mut edits = Vec::new();
+
                     let local_segment = &mut state.segments[index];
 
                     if !local_segment.editing_split {
@@ -54,9 +68,7 @@ pub(super) fn segments(ui: &mut Ui, state: &mut SplitsState) {
                         state.editor.select_only(index);
                     }
                     if name_editor.changed() {
-                        state.editor.select_only(index);
-                        state.editor.active_segment().set_name(&segment.name);
-                        state.dirty = true;
+                        edits.push(RunEdit::SegmentName(index, segment.name.clone()));
                     }
 
                     let split_editor = ui.text_edit_singleline(&mut local_segment.split);
@@ -70,14 +82,9 @@ pub(super) fn segments(ui: &mut Ui, state: &mut SplitsState) {
                     }
                     // dirty hack because egui lost_focus method ~~sucks balls~~ is extremely inconsistent
                     if !split_editor.has_focus() && local_segment.editing_split {
-                        state.editor.select_only(index);
-                        state
-                            .editor
-                            .active_segment()
-                            .parse_and_set_split_time(&local_segment.split)
-                            .ok();
                         local_segment.editing_split = false;
-                        state.dirty = true;
+                        let text = local_segment.split.clone();
+                        edits.push(RunEdit::SplitTime(index, method, text));
                     }
 
                     let segment_editor = ui.text_edit_singleline(&mut local_segment.segment);
@@ -91,14 +98,9 @@ pub(super) fn segments(ui: &mut Ui, state: &mut SplitsState) {
                     }
 
                     if !segment_editor.has_focus() && local_segment.editing_segment {
-                        state.editor.select_only(index);
-                        state
-                            .editor
-                            .active_segment()
-                            .parse_and_set_segment_time(&local_segment.segment)
-                            .ok();
                         local_segment.editing_segment = false;
-                        state.dirty = true;
+                        let text = local_segment.segment.clone();
+                        edits.push(RunEdit::SegmentTime(index, method, text));
                     }
 
                     let best_segment_editor =
@@ -112,18 +114,78 @@ pub(super) fn segments(ui: &mut Ui, state: &mut SplitsState) {
                     }
 
                     if !best_segment_editor.has_focus() && local_segment.editing_best_segment {
-                        state.editor.select_only(index);
-                        state
-                            .editor
-                            .active_segment()
-                            .parse_and_set_best_segment_time(&local_segment.best_segment)
-                            .ok();
                         local_segment.editing_best_segment = false;
-                        state.dirty = true;
+                        let text = local_segment.best_segment.clone();
+                        edits.push(RunEdit::BestSegmentTime(index, method, text));
+                    }
+
+                    let row = [
+                        name_editor.id,
+                        split_editor.id,
+                        segment_editor.id,
+                        best_segment_editor.id,
+                    ];
+
+                    // tabbing into a row selects it, like clicking does
+                    if [
+                        &name_editor,
+                        &split_editor,
+                        &segment_editor,
+                        &best_segment_editor,
+                    ]
+                    .iter()
+                    .any(|response| response.gained_focus())
+                    {
+                        state.editor.select_only(index);
                     }
 
+                    rows.push(row);
+
                     ui.end_row();
+
+                    for edit in edits {
+                        state.edit(edit);
+                    }
                 }
             });
         });
+
+    move_between_rows(ui, state, &rows, active);
+}
+
+// up and down move to the same field in the row above or below, or move the selection when no
+// field is focused
+fn move_between_rows(ui: &Ui, state: &mut SplitsState, rows: &[[Id; 4]], active: Option<usize>) {
+    let up = ui.input().key_pressed(Key::ArrowUp);
+
+    if !up && !ui.input().key_pressed(Key::ArrowDown) {
+        return;
+    }
+
+    let focused = ui.memory().focus().and_then(|focused| {
+        rows.iter().enumerate().find_map(|(row, ids)| {
+            let column = ids.iter().position(|id| *id == focused)?;
+            Some((row, column))
+        })
+    });
+
+    let (row, column) = match (focused, active) {
+        (Some((row, column)), _) => (row, Some(column)),
+        (None, Some(row)) if !ui.ctx().wants_keyboard_input() => (row, None),
+        _ => return,
+    };
+
+    let target = if up {
+        row.checked_sub(1)
+    } else {
+        Some(row + 1).filter(|row| *row < rows.len())
+    };
+
+    if let Some(target) = target {
+        state.editor.select_only(target);
+
+        if let Some(column) = column {
+            ui.memory().request_focus(rows[target][column]);
+        }
+    }
 }
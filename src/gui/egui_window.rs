@@ -0,0 +1,204 @@
+use egui_glow::{
+    egui_winit::egui::{self, FontData, FontDefinitions, FontFamily},
+    EguiGlow,
+};
+use glutin::{
+    event::WindowEvent,
+    event_loop::EventLoopWindowTarget,
+    window::{Window, WindowBuilder, WindowId},
+    ContextWrapper, PossiblyCurrent,
+};
+use std::rc::Rc;
+
+use crate::UserEvent;
+
+use super::appearance::{self, Appearance};
+
+const ARIAL: &[u8] = include_bytes!("../arial.ttf");
+
+/// An OpenGL window that egui draws into. Shared by all of our egui based windows.
+pub(crate) struct EguiWindow {
+    // only ever None while we swap the current context
+    gl_window: Option<ContextWrapper<PossiblyCurrent, Window>>,
+    egui_glow: EguiGlow,
+    gl: Rc<glow::Context>,
+    clear_color: [f32; 3],
+    // the one that was last set, None if it has to be set again
+    appearance: Option<Appearance>,
+    scale: f32,
+}
+
+impl EguiWindow {
+    pub fn new(
+        wb: WindowBuilder,
+        event_loop: &EventLoopWindowTarget<UserEvent>,
+        clear_color: [f32; 3],
+    ) -> Self {
+        let gl_window = unsafe {
+            glutin::ContextBuilder::new()
+                .with_depth_buffer(0)
+                .with_srgb(true)
+                .with_stencil_buffer(0)
+                .with_vsync(true)
+                .build_windowed(wb, event_loop)
+                .unwrap()
+                .make_current()
+                .unwrap()
+        };
+
+        let gl = Rc::new(unsafe {
+            glow::Context::from_loader_function(|s| gl_window.get_proc_address(s))
+        });
+
+        let mut egui_glow = EguiGlow::new(gl_window.window(), gl.clone());
+
+        EguiWindow::egui_setup(&mut egui_glow.egui_ctx, None);
+
+        EguiWindow {
+            gl_window: Some(gl_window),
+            egui_glow,
+            gl,
+            clear_color,
+            appearance: None,
+            scale: 1.,
+        }
+    }
+
+    pub fn window(&self) -> &Window {
+        self.gl_window.as_ref().unwrap().window()
+    }
+
+    pub fn id(&self) -> WindowId {
+        self.window().id()
+    }
+
+    /// Whether a widget has the keyboard focus, e.g. a text field being typed in.
+    pub fn has_focused_widget(&self) -> bool {
+        self.egui_glow.egui_ctx.wants_keyboard_input()
+    }
+
+    /// Sets the theme, scale and font, if they changed since the last time.
+    pub fn set_appearance(&mut self, appearance: &Appearance) {
+        if self.appearance.as_ref() == Some(appearance) {
+            return;
+        }
+
+        let visuals = appearance.visuals(self.window());
+        self.clear_color = appearance::clear_color(&visuals);
+        self.egui_glow.egui_ctx.set_visuals(visuals);
+
+        // loading the font again every time the theme changes would be a waste
+        let font_changed = self
+            .appearance
+            .as_ref()
+            .map_or(appearance.font.is_some(), |old| old.font != appearance.font);
+
+        if font_changed {
+            EguiWindow::egui_setup(&mut self.egui_glow.egui_ctx, appearance.font_data());
+        }
+
+        self.scale = appearance.scale;
+        self.appearance = Some(appearance.clone());
+
+        self.window().request_redraw();
+    }
+
+    pub fn on_event(&mut self, event: &WindowEvent) {
+        self.egui_glow.on_event(event);
+
+        if let WindowEvent::ThemeChanged(_) = event {
+            // the system theme might be the one we use
+            self.appearance = None;
+        }
+
+        self.window().request_redraw();
+    }
+
+    pub fn redraw(&mut self, run_ui: impl FnMut(&egui::Context)) {
+        // with more than one of these windows open the last one created would be current
+        self.make_current();
+
+        let gl_window = self.gl_window.as_ref().unwrap();
+
+        // egui_winit goes back to the monitor's scale factor when it changes. it picks this one up
+        // after the frame, so a new scale is off for one frame
+        self.egui_glow
+            .egui_ctx
+            .set_pixels_per_point(gl_window.window().scale_factor() as f32 * self.scale);
+
+        let needs_repaint = self.egui_glow.run(gl_window.window(), run_ui);
+
+        if needs_repaint {
+            unsafe {
+                use glow::HasContext as _;
+                self.gl.clear_color(
+                    self.clear_color[0],
+                    self.clear_color[1],
+                    self.clear_color[2],
+                    1.0,
+                );
+
+                // idk what this does
+                self.gl.clear(glow::COLOR_BUFFER_BIT);
+            }
+
+            self.egui_glow.paint(gl_window.window());
+
+            gl_window.swap_buffers().unwrap();
+
+            gl_window.window().request_redraw();
+        }
+    }
+
+    fn make_current(&mut self) {
+        let gl_window = self.gl_window.take().unwrap();
+
+        if gl_window.is_current() {
+            self.gl_window = Some(gl_window);
+            return;
+        }
+
+        self.gl_window = Some(
+            unsafe { gl_window.make_current() }.unwrap_or_else(|(_, e)| {
+                panic!("Could not make OpenGL context current, got error {e}")
+            }),
+        );
+    }
+
+    // a custom font comes first, arial is still there for what it doesn't have
+    fn egui_setup(ctx: &mut egui::Context, custom_font: Option<Vec<u8>>) {
+        let mut fonts = FontDefinitions::default();
+
+        fonts
+            .font_data
+            .insert("arial".to_owned(), FontData::from_static(ARIAL));
+
+        if let Some(font) = custom_font {
+            fonts
+                .font_data
+                .insert("custom".to_owned(), FontData::from_owned(font));
+
+            for family in [FontFamily::Proportional, FontFamily::Monospace] {
+                fonts
+                    .families
+                    .get_mut(&family)
+                    .unwrap()
+                    .insert(0, "custom".to_owned());
+            }
+        }
+
+        fonts
+            .families
+            .get_mut(&FontFamily::Proportional)
+            .unwrap()
+            .push("arial".to_owned());
+
+        fonts
+            .families
+            .get_mut(&FontFamily::Monospace)
+            .unwrap()
+            .push("arial".to_owned());
+
+        ctx.set_fonts(fonts);
+    }
+}
@@ -1,7 +1,7 @@
 use std::{fs::File, io::BufWriter};
 
-use egui_glow::egui_winit::egui::Ui;
-use livesplit_core::{run::saver::livesplit::save_run, TimerPhase};
+use egui_glow::egui_winit::egui::{Id, Key, Response, Ui};
+use livesplit_core::run::saver::livesplit::save_run;
 use rfd::{FileDialog, MessageButtons, MessageDialog};
 
 use crate::{
@@ -9,10 +9,15 @@ use crate::{
     SharedState,
 };
 
-use super::{edit_splits::SplitsState, settings::SettingsState, Menu};
+use super::{
+    edit_splits::SplitsState, profiles::ProfilesState, settings::SettingsState,
+    statistics::StatisticsState, Menu,
+};
 
 pub(super) fn left_panel(ui: &mut Ui, menu: &mut Menu, shared_state: &mut SharedState) {
-    if ui.button("Load Layout").clicked() {
+    let mut buttons = Vec::new();
+
+    if button(ui, &mut buttons, "Load Layout").clicked() {
         println!("loading layout");
         let path = FileDialog::new()
             .add_filter("LiveSplit Layout File", &["lsl"])
@@ -23,7 +28,7 @@ pub(super) fn left_panel(ui: &mut Ui, menu: &mut Menu, shared_state: &mut Shared
                 Ok(layout) => {
                     println!("Layout loading successful");
                     shared_state.layout = layout;
-                    shared_state.config.layout_path = Some(path);
+                    shared_state.config.profile_mut().layout_path = Some(path);
                 }
                 Err(e) => {
                     MessageDialog::new()
@@ -31,11 +36,10 @@ pub(super) fn left_panel(ui: &mut Ui, menu: &mut Menu, shared_state: &mut Shared
                         .set_description(format!("Failed to load layout, got error {e}").as_str());
                 }
             }
-        } else {
         }
     }
 
-    if ui.button("Load Splits").clicked() {
+    if button(ui, &mut buttons, "Load Splits").clicked() {
         println!("loading splits");
         let path = FileDialog::new()
             .add_filter("LiveSplit Splits File", &["lss"])
@@ -43,7 +47,10 @@ pub(super) fn left_panel(ui: &mut Ui, menu: &mut Menu, shared_state: &mut Shared
 
         if let Some(path) = path {
             if let Ok(splits) = load_splits_from_file(&path) {
-                shared_state.config.splits_path = Some(path);
+                shared_state.config.profile_mut().splits_path = Some(path);
+                shared_state
+                    .auto_splitter
+                    .load_for_splits(&shared_state.config);
 
                 println!("Split loading successful");
 
@@ -69,6 +76,8 @@ pub(super) fn left_panel(ui: &mut Ui, menu: &mut Menu, shared_state: &mut Shared
                     *menu = Menu::EditSplits(SplitsState::new(splits.clone()).into());
                 }
 
+                shared_state.finish_attempt();
+
                 let mut timer = shared_state.timer.write();
 
                 let should_save_splits = if timer.run().has_been_modified() {
@@ -98,23 +107,54 @@ pub(super) fn left_panel(ui: &mut Ui, menu: &mut Menu, shared_state: &mut Shared
         }
     }
 
-    if ui.button("Edit Splits").clicked() && menu.on_destroy(shared_state) {
-        if shared_state.timer.read().current_phase() == TimerPhase::NotRunning {
-            *menu =
-                Menu::EditSplits(SplitsState::new(shared_state.timer.read().run().clone()).into());
-        } else {
-            MessageDialog::new()
-                .set_title("Can't edit splits")
-                .set_description("You can't edit splits while the timer is running!+")
-                .show();
-        }
+    if button(ui, &mut buttons, "Edit Splits").clicked() && menu.on_destroy(shared_state) {
+        *menu = Menu::EditSplits(SplitsState::new(shared_state.timer.read().run().clone()).into());
     }
 
-    if ui.button("Edit Layout").clicked() && menu.on_destroy(shared_state) {
+    if button(ui, &mut buttons, "Edit Layout").clicked() && menu.on_destroy(shared_state) {
         *menu = Menu::EditLayout(Default::default());
     }
 
-    if ui.button("Settings").clicked() && menu.on_destroy(shared_state) {
-        *menu = Menu::Settings(SettingsState::new(shared_state.hotkey_system.config()).into());
+    if button(ui, &mut buttons, "Statistics").clicked() && menu.on_destroy(shared_state) {
+        *menu = Menu::Statistics(StatisticsState::new(shared_state).into());
+    }
+
+    if button(ui, &mut buttons, "Auto Splitter").clicked() && menu.on_destroy(shared_state) {
+        *menu = Menu::AutoSplitter;
+    }
+
+    if button(ui, &mut buttons, "Profiles").clicked() && menu.on_destroy(shared_state) {
+        *menu = Menu::Profiles(ProfilesState::default().into());
     }
+
+    if button(ui, &mut buttons, "Settings").clicked() && menu.on_destroy(shared_state) {
+        *menu = Menu::Settings(SettingsState::new(shared_state).into());
+    }
+
+    move_focus(ui, &buttons);
+}
+
+// remembers the button, so the arrow keys can move between them
+fn button(ui: &mut Ui, buttons: &mut Vec<Id>, text: &str) -> Response {
+    let response = ui.button(text);
+    buttons.push(response.id);
+    response
+}
+
+// up and down move the focus between the buttons, enter clicks them
+fn move_focus(ui: &Ui, buttons: &[Id]) {
+    let focused = match buttons.iter().position(|id| ui.memory().has_focus(*id)) {
+        Some(focused) => focused,
+        None => return,
+    };
+
+    let next = if ui.input().key_pressed(Key::ArrowDown) {
+        (focused + 1) % buttons.len()
+    } else if ui.input().key_pressed(Key::ArrowUp) {
+        (focused + buttons.len() - 1) % buttons.len()
+    } else {
+        return;
+    };
+
+    ui.memory().request_focus(buttons[next]);
 }
@@ -8,48 +8,125 @@ mod edit_layout;
 use edit_layout::edit_layout;
 mod edit_splits;
 use edit_splits::edit_splits;
-
-use egui_glow::{
-    egui_winit::egui::{self, FontData, FontDefinitions, FontFamily},
-    EguiGlow,
-};
+pub use edit_splits::remove_attempt;
+mod statistics;
+use statistics::statistics;
+mod auto_splitter;
+use auto_splitter::auto_splitter;
+pub(crate) mod profiles;
+use profiles::profiles;
+
+pub mod appearance;
+mod egui_window;
+pub(crate) use egui_window::EguiWindow;
+
+use egui_glow::egui_winit::egui;
 use glutin::{
-    event::{KeyboardInput, VirtualKeyCode, WindowEvent},
+    event::{ElementState, KeyboardInput, VirtualKeyCode, WindowEvent},
     event_loop::EventLoopWindowTarget,
-    window::{Window, WindowBuilder, WindowId},
-    ContextWrapper, PossiblyCurrent,
+    window::{WindowBuilder, WindowId},
 };
-use std::rc::Rc;
+use livesplit_core::{Run, TimerPhase};
 
 use crate::{window::ApplicationWindow, SharedState, UserEvent};
 
-use self::{edit_layout::LayoutState, edit_splits::SplitsState, settings::SettingsState};
+use self::{
+    appearance::Appearance, edit_layout::LayoutState, edit_splits::SplitsState,
+    profiles::ProfilesState, settings::SettingsState, statistics::StatisticsState,
+};
 
 enum Menu {
     Main,
     Settings(Box<SettingsState>),
     EditSplits(Box<SplitsState>),
     EditLayout(Box<LayoutState>),
+    Statistics(Box<StatisticsState>),
+    AutoSplitter,
+    Profiles(Box<ProfilesState>),
 }
 
 impl Menu {
     fn on_destroy(&mut self, shared_state: &mut SharedState) -> bool {
         match self {
-            Menu::Main => true,
+            Menu::Main | Menu::AutoSplitter => true,
             Menu::Settings(state) => state.on_destroy(shared_state),
             Menu::EditSplits(state) => state.on_destroy(shared_state),
             Menu::EditLayout(state) => state.on_destroy(shared_state),
+            Menu::Statistics(state) => state.on_destroy(shared_state),
+            Menu::Profiles(state) => state.on_destroy(shared_state),
         }
     }
 }
-const ARIAL: &[u8] = include_bytes!("../arial.ttf");
+/// Everything the configuration window shows, apart from the window itself. It can be drawn into
+/// any egui context, which lets tests drive it without a window.
+pub struct ConfigurationUi {
+    current_menu: Menu,
+}
+
+impl Default for ConfigurationUi {
+    fn default() -> Self {
+        Self {
+            current_menu: Menu::Main,
+        }
+    }
+}
+
+impl ConfigurationUi {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, shared_state: &mut SharedState) {
+        let menu = &mut self.current_menu;
+
+        egui::SidePanel::left("Left Panel").show(ctx, |ui| left_panel(ui, menu, shared_state));
+        egui::CentralPanel::default().show(ctx, |ui| {
+            // the splits editor knows itself what can change during an attempt
+            let read_only = shared_state.timer.read().current_phase() != TimerPhase::NotRunning
+                && !matches!(menu, Menu::Main | Menu::EditSplits(_));
+
+            if read_only {
+                ui.label("The timer is running, this can be changed after the reset.");
+            }
+
+            ui.add_enabled_ui(!read_only, |ui| match menu {
+                Menu::Main => {}
+                Menu::Settings(state) => hotkey_component(ui, shared_state, state),
+                Menu::EditLayout(_) => edit_layout(ui),
+                Menu::EditSplits(state) => edit_splits(ui, shared_state, state),
+                Menu::Statistics(state) => statistics(ui, shared_state, state),
+                Menu::AutoSplitter => auto_splitter(ui, shared_state),
+                Menu::Profiles(state) => profiles(ui, shared_state, state),
+            });
+        });
+    }
+
+    // returns true if the current menu is fine with being closed
+    pub fn on_destroy(&mut self, shared_state: &mut SharedState) -> bool {
+        self.current_menu.on_destroy(shared_state)
+    }
+
+    /// The run in the splits editor, if it's open.
+    pub fn edited_run(&self) -> Option<&Run> {
+        match &self.current_menu {
+            Menu::EditSplits(state) => Some(state.run()),
+            _ => None,
+        }
+    }
+
+    /// Whether the splits editor has changes that weren't saved yet.
+    pub fn has_unsaved_changes(&self) -> bool {
+        match &self.current_menu {
+            Menu::EditSplits(state) => state.dirty(),
+            _ => false,
+        }
+    }
+}
+
 pub struct ConfigurationWindow {
-    pub gl_window: ContextWrapper<PossiblyCurrent, Window>,
-    pub egui_glow: EguiGlow,
-    gl: Rc<glow::Context>,
-    clear_color: [f32; 3],
+    window: EguiWindow,
 
-    current_menu: Menu,
+    ui: ConfigurationUi,
 }
 
 impl ApplicationWindow for ConfigurationWindow {
@@ -57,60 +134,43 @@ impl ApplicationWindow for ConfigurationWindow {
         if let WindowEvent::KeyboardInput {
             input:
                 KeyboardInput {
+                    state: ElementState::Pressed,
                     virtual_keycode: Some(VirtualKeyCode::Escape),
                     ..
                 },
             ..
         } = event
         {
-            shared_state
-                .send_event
-                .send_event(UserEvent::DestroyWindow(self.id()))
-                .ok();
+            // the first escape only leaves the focused field, egui takes care of that
+            if !self.window.has_focused_widget() {
+                shared_state
+                    .send_event
+                    .send_event(UserEvent::DestroyWindow(self.id()))
+                    .ok();
+            }
         }
 
-        self.egui_glow.on_event(&event);
-
-        self.gl_window.window().request_redraw();
+        self.window.on_event(&event);
     }
 
     fn redraw(&mut self, shared_state: &mut SharedState) {
-        let needs_repaint = self.egui_glow.run(self.gl_window.window(), |ctx| {
-            ConfigurationWindow::egui(ctx, &mut self.current_menu, shared_state);
-        });
-
-        if needs_repaint {
-            unsafe {
-                use glow::HasContext as _;
-                self.gl.clear_color(
-                    self.clear_color[0],
-                    self.clear_color[1],
-                    self.clear_color[2],
-                    1.0,
-                );
-
-                // idk what this does
-                self.gl.clear(glow::COLOR_BUFFER_BIT);
-            }
+        self.window.set_appearance(&shared_state.config.appearance);
 
-            self.egui_glow.paint(self.gl_window.window());
+        let ui = &mut self.ui;
 
-            self.gl_window.swap_buffers().unwrap();
-
-            self.request_redraw();
-        }
+        self.window.redraw(|ctx| ui.show(ctx, shared_state));
     }
 
     fn id(&self) -> WindowId {
-        self.gl_window.window().id()
+        self.window.id()
     }
 
     fn request_redraw(&mut self) {
-        self.gl_window.window().request_redraw();
+        self.window.window().request_redraw();
     }
 
     fn on_destroy(&mut self, shared_state: &mut SharedState) -> bool {
-        let ret = self.current_menu.on_destroy(shared_state);
+        let ret = self.ui.on_destroy(shared_state);
 
         if ret {
             shared_state.has_configuration_window = false;
@@ -121,70 +181,19 @@ impl ApplicationWindow for ConfigurationWindow {
 }
 
 impl ConfigurationWindow {
-    pub fn new(event_loop: &EventLoopWindowTarget<UserEvent>) -> Self {
+    pub fn new(event_loop: &EventLoopWindowTarget<UserEvent>, appearance: &Appearance) -> Self {
         let wb = WindowBuilder::new().with_title("LiveSplit One Configuration");
-        let gl_window = unsafe {
-            glutin::ContextBuilder::new()
-                .with_depth_buffer(0)
-                .with_srgb(true)
-                .with_stencil_buffer(0)
-                .with_vsync(true)
-                .build_windowed(wb, event_loop)
-                .unwrap()
-                .make_current()
-                .unwrap()
-        };
-
-        let gl = Rc::new(unsafe {
-            glow::Context::from_loader_function(|s| gl_window.get_proc_address(s))
-        });
 
-        let mut egui_glow = EguiGlow::new(gl_window.window(), gl.clone());
-
-        ConfigurationWindow::egui_setup(&mut egui_glow.egui_ctx);
+        let mut window = EguiWindow::new(wb, event_loop, [0., 0., 0.]);
+        window.set_appearance(appearance);
 
         ConfigurationWindow {
-            gl_window,
-            egui_glow,
-            gl,
-            current_menu: Menu::Main,
-            clear_color: [0., 1., 0.1],
+            window,
+            ui: ConfigurationUi::new(),
         }
     }
 
     pub fn id(&self) -> WindowId {
-        self.gl_window.window().id()
-    }
-
-    fn egui(ctx: &egui::Context, menu: &mut Menu, shared_state: &mut SharedState) {
-        egui::SidePanel::left("Left Panel").show(ctx, |ui| left_panel(ui, menu, shared_state));
-        egui::CentralPanel::default().show(ctx, |ui| match menu {
-            Menu::Main => {}
-            Menu::Settings(state) => hotkey_component(ui, shared_state, state),
-            Menu::EditLayout(_) => edit_layout(ui),
-            Menu::EditSplits(state) => edit_splits(ui, shared_state, state),
-        });
-    }
-
-    fn egui_setup(ctx: &mut egui::Context) {
-        let mut fonts = FontDefinitions::default();
-
-        fonts
-            .font_data
-            .insert("arial".to_owned(), FontData::from_static(ARIAL));
-
-        fonts
-            .families
-            .get_mut(&FontFamily::Proportional)
-            .unwrap()
-            .push("arial".to_owned());
-
-        fonts
-            .families
-            .get_mut(&FontFamily::Monospace)
-            .unwrap()
-            .push("arial".to_owned());
-
-        ctx.set_fonts(fonts);
+        self.window.id()
     }
 }
@@ -0,0 +1,133 @@
+use std::mem::take;
+
+use egui_glow::egui_winit::egui::{Button, Grid, Ui};
+use rfd::{MessageDialog, MessageLevel};
+
+use crate::SharedState;
+
+#[derive(Default)]
+pub(super) struct ProfilesState {
+    new_name: String,
+    // what's typed into the name of each profile, it's only the name once it's done and usable
+    names: Vec<String>,
+}
+
+impl ProfilesState {
+    pub fn on_destroy(&mut self, _shared_state: &mut SharedState) -> bool {
+        // nop
+        true
+    }
+}
+
+pub(crate) fn show_switch_error(e: &str) {
+    MessageDialog::new()
+        .set_title("Failed to Switch Profile")
+        .set_level(MessageLevel::Error)
+        .set_description(e)
+        .show();
+}
+
+pub(super) fn profiles(ui: &mut Ui, shared_state: &mut SharedState, state: &mut ProfilesState) {
+    ui.heading("Profiles");
+    ui.label("Every profile has its own splits, layout, hotkeys and size.");
+
+    let current = shared_state.config.current_profile;
+    let can_remove = shared_state.config.profiles.len() > 1;
+
+    let names: Vec<String> = shared_state
+        .config
+        .profiles
+        .iter()
+        .map(|profile| profile.name.clone())
+        .collect();
+
+    // a profile was added or removed
+    if state.names.len() != names.len() {
+        state.names = names.clone();
+    }
+
+    let mut switch_to = None;
+    let mut remove = None;
+
+    Grid::new("profiles").show(ui, |ui| {
+        for (index, profile) in shared_state.config.profiles.iter_mut().enumerate() {
+            let name = &mut state.names[index];
+
+            if ui.text_edit_singleline(name).lost_focus() {
+                let others = names
+                    .iter()
+                    .enumerate()
+                    .filter(|&(other, _)| other != index)
+                    .map(|(_, other)| other.as_str());
+
+                if is_usable_name(name, others) {
+                    profile.name = name.clone();
+                } else {
+                    *name = profile.name.clone();
+                }
+            }
+
+            match &profile.splits_path {
+                Some(path) => ui.label(path.display().to_string()),
+                None => ui.label("No splits"),
+            };
+
+            let label = if index == current {
+                "Current"
+            } else {
+                "Switch"
+            };
+
+            if ui
+                .add_enabled(index != current, Button::new(label))
+                .clicked()
+            {
+                switch_to = Some(index);
+            }
+
+            // the current profile is what the timer is using right now
+            if ui
+                .add_enabled(can_remove && index != current, Button::new("Remove"))
+                .clicked()
+            {
+                remove = Some(index);
+            }
+
+            ui.end_row();
+        }
+    });
+
+    if let Some(index) = remove {
+        shared_state.config.profiles.remove(index);
+
+        if index < current {
+            shared_state.config.current_profile -= 1;
+        }
+    }
+
+    if let Some(index) = switch_to {
+        if let Err(e) = shared_state.switch_profile(index) {
+            show_switch_error(&e);
+        }
+    }
+
+    ui.horizontal(|ui| {
+        ui.label("Name: ");
+        ui.text_edit_singleline(&mut state.new_name);
+
+        let usable = is_usable_name(&state.new_name, names.iter().map(String::as_str));
+
+        if ui.add_enabled(usable, Button::new("New Profile")).clicked() {
+            // a new profile starts out as a copy of the current one
+            let mut profile = shared_state.config.profile().clone();
+            profile.name = take(&mut state.new_name);
+
+            shared_state.config.profiles.push(profile);
+        }
+    });
+}
+
+// the name is what tells the profiles apart
+fn is_usable_name<'a>(name: &str, others: impl IntoIterator<Item = &'a str>) -> bool {
+    !name.trim().is_empty() && others.into_iter().all(|other| other != name)
+}
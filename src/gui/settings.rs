@@ -1,26 +1,50 @@
-use egui_glow::egui_winit::egui::{ComboBox, DragValue, Ui};
+use std::{
+    fmt::{Debug, Display},
+    hash::Hash,
+    io,
+    net::Ipv4Addr,
+    str::FromStr,
+};
+
+use egui_glow::egui_winit::egui::{Button, Checkbox, ComboBox, DragValue, TextEdit, Ui};
 use enum_map::EnumMap;
-use livesplit_core::{hotkey::KeyCode, HotkeyConfig};
-use rfd::{MessageDialog, MessageLevel};
+use livesplit_core::{hotkey::KeyCode, TimerPhase, TimingMethod};
+use rfd::{FileDialog, MessageDialog, MessageLevel};
 
 use crate::{
+    configuration::{MAX_ZOOM, MIN_ZOOM},
     hotkey::{Hotkey, KEY_CODES},
+    reset::ResetPolicy,
+    server::Server,
+    utils::timing_method_name,
+    websocket::WebSocketServer,
     SharedState, UserEvent,
 };
 
+use super::appearance::{load_font, show_font_error, Theme, SCALES};
+
 pub struct SettingsState {
     hotkey_state: EnumMap<Hotkey, Option<KeyCode>>,
+    server_address: String,
+    websocket_address: String,
 }
 
 impl SettingsState {
-    pub fn new(hotkey_config: HotkeyConfig) -> Self {
+    pub fn new(shared_state: &SharedState) -> Self {
+        let hotkey_config = shared_state.hotkeys.config();
+        let extra_hotkey_config = shared_state.hotkeys.extra_config();
+
         let mut hotkey_state: EnumMap<Hotkey, Option<KeyCode>> = Default::default();
 
         for (hotkey, keycode) in hotkey_state.iter_mut() {
-            *keycode = hotkey.get_keycode(&hotkey_config);
+            *keycode = hotkey.get_keycode(&hotkey_config, &extra_hotkey_config);
         }
 
-        Self { hotkey_state }
+        Self {
+            hotkey_state,
+            server_address: shared_state.config.server.address.to_string(),
+            websocket_address: shared_state.config.websocket.address.to_string(),
+        }
     }
 
     pub fn on_destroy(&self, _shared_state: &SharedState) -> bool {
@@ -51,27 +75,37 @@ fn row(ui: &mut Ui, hotkey: Hotkey, _shared_state: &mut SharedState, state: &mut
 }
 
 fn update_hotkeys(shared_state: &mut SharedState, state: &mut SettingsState) {
-    let mut hotkey_config = shared_state.hotkey_system.config();
+    let mut hotkey_config = shared_state.hotkeys.config();
+    let mut extra_hotkey_config = shared_state.hotkeys.extra_config();
 
     let mut dirty = false;
 
     for (hotkey, keycode) in state.hotkey_state {
-        if keycode != hotkey.get_keycode(&hotkey_config) {
-            hotkey.set_keycode(&mut hotkey_config, keycode);
+        if keycode != hotkey.get_keycode(&hotkey_config, &extra_hotkey_config) {
+            hotkey.set_keycode(&mut hotkey_config, &mut extra_hotkey_config, keycode);
             dirty = true;
         }
     }
 
     if dirty {
-        match shared_state.hotkey_system.set_config(hotkey_config) {
+        let result = shared_state
+            .hotkeys
+            .set_config(hotkey_config, extra_hotkey_config);
+
+        match result {
             Ok(()) => {
                 //update config
-                shared_state.config.hotkey_config = hotkey_config;
+                let profile = shared_state.config.profile_mut();
+                profile.hotkey_config = hotkey_config;
+                profile.extra_hotkey_config = extra_hotkey_config;
             }
             Err(e) => {
-                // reset our state back to the hotkey state
+                // reset our state back to the hotkeys that are still registered
+                let hotkey_config = shared_state.hotkeys.config();
+                let extra_hotkey_config = shared_state.hotkeys.extra_config();
+
                 for (hotkey, keycode) in state.hotkey_state.iter_mut() {
-                    *keycode = hotkey.get_keycode(&hotkey_config);
+                    *keycode = hotkey.get_keycode(&hotkey_config, &extra_hotkey_config);
                 }
 
                 // then show an error
@@ -94,6 +128,7 @@ pub(super) fn hotkey_component(
 
     row(ui, Hotkey::Split, shared_state, state);
     row(ui, Hotkey::Reset, shared_state, state);
+    row(ui, Hotkey::ResetAndDiscard, shared_state, state);
     row(ui, Hotkey::Undo, shared_state, state);
     row(ui, Hotkey::Skip, shared_state, state);
     row(ui, Hotkey::Pause, shared_state, state);
@@ -101,50 +136,293 @@ pub(super) fn hotkey_component(
     row(ui, Hotkey::PreviousComparison, shared_state, state);
     row(ui, Hotkey::NextComparison, shared_state, state);
     row(ui, Hotkey::ToggleTimingMethod, shared_state, state);
+    row(ui, Hotkey::InitializeGameTime, shared_state, state);
+    row(ui, Hotkey::PauseGameTime, shared_state, state);
+    row(ui, Hotkey::ResumeGameTime, shared_state, state);
+    row(ui, Hotkey::TogglePracticeMode, shared_state, state);
 
     update_hotkeys(shared_state, state);
 
+    ui.heading("Timing Method");
+
+    ui.horizontal(|ui| {
+        let mut timer = shared_state.timer.write();
+        let current = timer.current_timing_method();
+
+        for method in [TimingMethod::RealTime, TimingMethod::GameTime] {
+            if ui
+                .radio(current == method, timing_method_name(method))
+                .clicked()
+            {
+                timer.set_current_timing_method(method);
+            }
+        }
+    });
+
+    ui.heading("Resetting");
+
+    for policy in [
+        ResetPolicy::AskOnNewBests,
+        ResetPolicy::AlwaysUpdate,
+        ResetPolicy::NeverUpdate,
+    ] {
+        ui.radio_value(
+            &mut shared_state.config.reset_policy,
+            policy,
+            policy.description(),
+        );
+    }
+
+    ui.heading("Practice Mode");
+
+    let mut practice_mode = shared_state.practice_mode;
+    let between_attempts = shared_state.timer.read().current_phase() == TimerPhase::NotRunning;
+
+    if ui
+        .add_enabled(
+            between_attempts,
+            Checkbox::new(
+                &mut practice_mode,
+                "Practice mode, attempts aren't recorded",
+            ),
+        )
+        .changed()
+    {
+        shared_state.toggle_practice_mode().ok();
+    }
+
+    ui.checkbox(
+        &mut shared_state.config.practice_log,
+        "Keep a log of practice attempts",
+    );
+
+    ui.heading("Accessibility");
+
+    ui.checkbox(
+        &mut shared_state.config.accessibility.describe_in_title,
+        "Describe the timer in the window title",
+    );
+    ui.checkbox(
+        &mut shared_state.config.accessibility.speak,
+        "Announce splits, golds and personal bests out loud",
+    );
+
+    appearance_settings(ui, shared_state);
+
+    server_settings(ui, shared_state, state);
+
     ui.heading("Size");
 
     ui.horizontal(|ui| {
         ui.label("Width: ");
-        ui.add(DragValue::from_get_set(|x| width_get_set(x, shared_state)));
+
+        let size = &mut shared_state.config.profile_mut().size;
+        if number_edit(ui, "width", &mut size.0) {
+            size.0 = size.0.max(1);
+            shared_state.send_event.send_event(UserEvent::Resize).ok();
+        }
     });
 
     ui.horizontal(|ui| {
         ui.label("Height: ");
-        ui.add(DragValue::from_get_set(|x| height_get_set(x, shared_state)));
+
+        let size = &mut shared_state.config.profile_mut().size;
+        if number_edit(ui, "height", &mut size.1) {
+            size.1 = size.1.max(1);
+            shared_state.send_event.send_event(UserEvent::Resize).ok();
+        }
     });
-}
 
-fn height_get_set(x: Option<f64>, shared_state: &mut SharedState) -> f64 {
-    match x {
-        Some(height) => {
-            shared_state.config.size.1 = height.round() as u32;
+    ui.horizontal(|ui| {
+        ui.label("Zoom: ");
 
-            if shared_state.config.size.1 == 0 {
-                shared_state.config.size.1 = 1;
-            }
+        let zoom = DragValue::new(&mut shared_state.config.profile_mut().zoom)
+            .speed(0.05)
+            .clamp_range(MIN_ZOOM..=MAX_ZOOM)
+            .suffix("x");
 
+        if ui.add(zoom).changed() {
             shared_state.send_event.send_event(UserEvent::Resize).ok();
-            height
         }
-        None => shared_state.config.size.1.into(),
+    });
+}
+
+// egui's drag values share the text being typed into them, so tabbing from one to the next
+// carried the number over. this keeps the text per field and only takes it once it's done.
+// returns true if the value changed
+fn number_edit<T: FromStr + Display + PartialEq>(
+    ui: &mut Ui,
+    id_source: impl Hash + Debug,
+    value: &mut T,
+) -> bool {
+    let id = ui.make_persistent_id(id_source);
+    let mut text = ui
+        .data()
+        .get_temp::<String>(id)
+        .unwrap_or_else(|| value.to_string());
+
+    let response = ui.add(TextEdit::singleline(&mut text).id(id).desired_width(60.));
+
+    if response.has_focus() {
+        ui.data().insert_temp(id, text);
+        return false;
+    }
+
+    ui.data().remove::<String>(id);
+
+    match text.parse() {
+        Ok(new_value) if response.lost_focus() && new_value != *value => {
+            *value = new_value;
+            true
+        }
+        _ => false,
     }
 }
 
-fn width_get_set(x: Option<f64>, shared_state: &mut SharedState) -> f64 {
-    match x {
-        Some(width) => {
-            shared_state.config.size.0 = width.round() as u32;
+// returns true if any of the settings changed
+fn listen_settings(
+    ui: &mut Ui,
+    name: &str,
+    enabled: &mut bool,
+    address: &mut Ipv4Addr,
+    port: &mut u16,
+    address_text: &mut String,
+) -> bool {
+    let mut changed = false;
+
+    changed |= ui.checkbox(enabled, "Enabled").changed();
 
-            if shared_state.config.size.0 == 0 {
-                shared_state.config.size.0 = 1;
+    ui.horizontal(|ui| {
+        ui.label("Address: ");
+
+        let text = ui.add(TextEdit::singleline(address_text).desired_width(120.));
+
+        if text.lost_focus() {
+            match address_text.parse() {
+                Ok(new_address) if new_address != *address => {
+                    *address = new_address;
+                    changed = true;
+                }
+                Ok(_) => {}
+                Err(_) => *address_text = address.to_string(),
             }
+        }
 
-            shared_state.send_event.send_event(UserEvent::Resize).ok();
-            width
+        ui.label("Port: ");
+        changed |= number_edit(ui, (name, "port"), port);
+    });
+
+    changed
+}
+
+fn show_server_error(e: io::Error) {
+    MessageDialog::new()
+        .set_title("Failed to Start Server")
+        .set_level(MessageLevel::Error)
+        .set_description(format!("Could not start server, got error {e}").as_str())
+        .show();
+}
+
+fn appearance_settings(ui: &mut Ui, shared_state: &mut SharedState) {
+    ui.heading("Appearance");
+
+    let appearance = &mut shared_state.config.appearance;
+
+    ui.horizontal(|ui| {
+        for &theme in Theme::AVAILABLE {
+            ui.radio_value(&mut appearance.theme, theme, theme.name());
+        }
+    });
+
+    ComboBox::from_label("Scale")
+        .selected_text(format!("{}%", (appearance.scale * 100.).round()))
+        .show_ui(ui, |ui| {
+            for scale in SCALES {
+                ui.selectable_value(
+                    &mut appearance.scale,
+                    scale,
+                    format!("{}%", (scale * 100.).round()),
+                );
+            }
+        });
+
+    ui.horizontal(|ui| {
+        let font = match &appearance.font {
+            Some(path) => path
+                .file_name()
+                .unwrap_or(path.as_os_str())
+                .to_string_lossy()
+                .into_owned(),
+            None => "Arial".to_owned(),
+        };
+        ui.label(format!("Font: {font}"));
+
+        if ui.button("Choose...").clicked() {
+            let path = FileDialog::new()
+                .add_filter("Font", &["ttf", "otf"])
+                .pick_file();
+
+            // a broken font would crash every egui window, and it's saved in the config
+            if let Some(path) = path {
+                match load_font(&path) {
+                    Ok(_) => appearance.font = Some(path),
+                    Err(e) => show_font_error(&path, e),
+                }
+            }
+        }
+
+        if ui
+            .add_enabled(appearance.font.is_some(), Button::new("Default"))
+            .clicked()
+        {
+            appearance.font = None;
+        }
+    });
+}
+
+fn server_settings(ui: &mut Ui, shared_state: &mut SharedState, state: &mut SettingsState) {
+    ui.heading("LiveSplit Server");
+
+    let config = &mut shared_state.config.server;
+
+    if listen_settings(
+        ui,
+        "server",
+        &mut config.enabled,
+        &mut config.address,
+        &mut config.port,
+        &mut state.server_address,
+    ) {
+        // the old server has to go first so the new one can bind the port
+        shared_state.server = None;
+
+        if config.enabled {
+            match Server::start(config, shared_state.send_event.clone()) {
+                Ok(server) => shared_state.server = Some(server),
+                Err(e) => show_server_error(e),
+            }
+        }
+    }
+
+    ui.heading("WebSocket Server");
+
+    let config = &mut shared_state.config.websocket;
+
+    if listen_settings(
+        ui,
+        "websocket",
+        &mut config.enabled,
+        &mut config.address,
+        &mut config.port,
+        &mut state.websocket_address,
+    ) {
+        shared_state.websocket_server = None;
+
+        if config.enabled {
+            match WebSocketServer::start(config) {
+                Ok(server) => shared_state.websocket_server = Some(server),
+                Err(e) => show_server_error(e),
+            }
         }
-        None => shared_state.config.size.0.into(),
     }
 }
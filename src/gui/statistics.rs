@@ -0,0 +1,296 @@
+use egui_glow::egui_winit::egui::{
+    plot::{Line, Plot, Points, Value, Values},
+    ComboBox, Grid, ScrollArea, Ui,
+};
+use livesplit_core::{analysis::sum_of_segments, Run, TimeSpan, TimingMethod};
+
+use crate::{
+    utils::{format_time, timing_method_name},
+    SharedState,
+};
+
+struct SegmentStatistics {
+    name: String,
+    average: Option<TimeSpan>,
+    median: Option<TimeSpan>,
+    standard_deviation: Option<TimeSpan>,
+    best: Option<TimeSpan>,
+    pb: Option<TimeSpan>,
+    resets: u32,
+    attempts_reached: u32,
+    // (attempt index, segment time in seconds) for the plot
+    history: Vec<(i32, f64)>,
+}
+
+impl SegmentStatistics {
+    fn reset_rate(&self) -> Option<f64> {
+        if self.attempts_reached == 0 {
+            None
+        } else {
+            Some(self.resets as f64 / self.attempts_reached as f64)
+        }
+    }
+
+    fn gold_gap(&self) -> Option<TimeSpan> {
+        Some(self.pb? - self.best?)
+    }
+}
+
+pub(super) struct StatisticsState {
+    timing_method: TimingMethod,
+    segments: Vec<SegmentStatistics>,
+    sum_of_best: Option<TimeSpan>,
+    best_possible_time: Option<TimeSpan>,
+    // (attempt index, final time in seconds) of every finished attempt
+    finished_attempts: Vec<(i32, f64)>,
+    selected_segment: usize,
+}
+
+impl StatisticsState {
+    pub fn new(shared_state: &SharedState) -> Self {
+        let timer = shared_state.timer.read();
+        let timing_method = timer.current_timing_method();
+
+        let mut state = Self::from_run(timer.run(), timing_method);
+
+        // while a run is in progress the best we can still do is the time we are at now plus the
+        // golds of every segment that is left
+        let current_split_index = timer.current_split_index();
+        if let Some(current_split_index) = current_split_index {
+            let elapsed = match current_split_index.checked_sub(1) {
+                Some(last_split) => timer.run().segment(last_split).split_time()[timing_method],
+                None => Some(TimeSpan::zero()),
+            };
+
+            state.best_possible_time = elapsed.and_then(|elapsed| {
+                timer.run().segments()[current_split_index..]
+                    .iter()
+                    .try_fold(elapsed, |sum, segment| {
+                        Some(sum + segment.best_segment_time()[timing_method]?)
+                    })
+            });
+        }
+
+        state
+    }
+
+    fn from_run(run: &Run, timing_method: TimingMethod) -> Self {
+        let mut segments = Vec::with_capacity(run.len());
+
+        let mut previous_pb_split = Some(TimeSpan::zero());
+
+        for segment in run.segments() {
+            let mut history: Vec<(i32, f64)> = segment
+                .segment_history()
+                .iter()
+                // entries with a non positive index were imported and don't belong to an attempt
+                .filter(|(index, _)| *index > 0)
+                .filter_map(|(index, time)| Some((*index, time[timing_method]?.total_seconds())))
+                .collect();
+
+            history.sort_by_key(|(index, _)| *index);
+
+            let pb_split = segment.personal_best_split_time()[timing_method];
+            let pb = match (previous_pb_split, pb_split) {
+                (Some(previous), Some(current)) => Some(current - previous),
+                _ => None,
+            };
+            previous_pb_split = pb_split;
+
+            let mut times: Vec<f64> = history.iter().map(|(_, time)| *time).collect();
+            times.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+            segments.push(SegmentStatistics {
+                name: segment.name().to_owned(),
+                average: average(&times).map(TimeSpan::from_seconds),
+                median: median(&times).map(TimeSpan::from_seconds),
+                standard_deviation: standard_deviation(&times).map(TimeSpan::from_seconds),
+                best: segment.best_segment_time()[timing_method],
+                pb,
+                resets: 0,
+                attempts_reached: 0,
+                history,
+            });
+        }
+
+        let mut finished_attempts = Vec::new();
+
+        for attempt in run.attempt_history() {
+            let index = attempt.index();
+
+            if let Some(time) = attempt.time()[timing_method] {
+                finished_attempts.push((index, time.total_seconds()));
+            }
+
+            // the segment history has one entry for every segment the attempt got through
+            let segments_passed = run
+                .segments()
+                .iter()
+                .take_while(|segment| segment.segment_history().get(index).is_some())
+                .count();
+
+            for segment in segments.iter_mut().take(segments_passed + 1) {
+                segment.attempts_reached += 1;
+            }
+
+            if attempt.time()[timing_method].is_none() {
+                if let Some(segment) = segments.get_mut(segments_passed) {
+                    segment.resets += 1;
+                }
+            }
+        }
+
+        let sum_of_best =
+            sum_of_segments::calculate_best(run.segments(), false, false, timing_method);
+
+        Self {
+            timing_method,
+            sum_of_best,
+            // when no run is in progress the best possible time is just the sum of best
+            best_possible_time: sum_of_best,
+            segments,
+            finished_attempts,
+            selected_segment: 0,
+        }
+    }
+
+    pub fn on_destroy(&mut self, _shared_state: &mut SharedState) -> bool {
+        // nop
+        true
+    }
+}
+
+fn average(times: &[f64]) -> Option<f64> {
+    if times.is_empty() {
+        None
+    } else {
+        Some(times.iter().sum::<f64>() / times.len() as f64)
+    }
+}
+
+// times has to be sorted
+fn median(times: &[f64]) -> Option<f64> {
+    let middle = times.len() / 2;
+
+    match times.len() {
+        0 => None,
+        len if len % 2 == 0 => Some((times[middle - 1] + times[middle]) / 2.),
+        _ => Some(times[middle]),
+    }
+}
+
+fn standard_deviation(times: &[f64]) -> Option<f64> {
+    let average = average(times)?;
+
+    let variance = times
+        .iter()
+        .map(|time| (time - average).powi(2))
+        .sum::<f64>()
+        / times.len() as f64;
+
+    Some(variance.sqrt())
+}
+
+pub(super) fn statistics(ui: &mut Ui, shared_state: &mut SharedState, state: &mut StatisticsState) {
+    ui.horizontal(|ui| {
+        ui.heading("Statistics");
+
+        ui.label(timing_method_name(state.timing_method));
+
+        if ui.button("Refresh").clicked() {
+            *state = StatisticsState::new(shared_state);
+        }
+    });
+
+    Grid::new("run statistics").show(ui, |ui| {
+        ui.label("Sum of Best");
+        ui.label(format_time(state.sum_of_best));
+        ui.end_row();
+
+        ui.label("Best Possible Time");
+        ui.label(format_time(state.best_possible_time));
+        ui.end_row();
+    });
+
+    ui.separator();
+
+    ScrollArea::both()
+        .max_height(0.5 * ui.available_height())
+        .show(ui, |ui| {
+            Grid::new("segment statistics")
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.heading("Segment Name");
+                    ui.heading("Average");
+                    ui.heading("Median");
+                    ui.heading("Std. Dev.");
+                    ui.heading("Best Segment");
+                    ui.heading("PB Segment");
+                    ui.heading("Gap to Gold");
+                    ui.heading("Reset Rate");
+                    ui.end_row();
+
+                    for segment in &state.segments {
+                        ui.label(&segment.name);
+                        ui.label(format_time(segment.average));
+                        ui.label(format_time(segment.median));
+                        ui.label(format_time(segment.standard_deviation));
+                        ui.label(format_time(segment.best));
+                        ui.label(format_time(segment.pb));
+                        ui.label(format_time(segment.gold_gap()));
+                        ui.label(match segment.reset_rate() {
+                            Some(rate) => format!(
+                                "{:.1}% ({}/{})",
+                                rate * 100.,
+                                segment.resets,
+                                segment.attempts_reached
+                            ),
+                            None => "-".to_owned(),
+                        });
+                        ui.end_row();
+                    }
+                });
+        });
+
+    ui.separator();
+
+    if state.segments.is_empty() {
+        return;
+    }
+
+    ComboBox::from_label("Plot")
+        .selected_text(&state.segments[state.selected_segment].name)
+        .show_ui(ui, |ui| {
+            for (index, segment) in state.segments.iter().enumerate() {
+                ui.selectable_value(&mut state.selected_segment, index, &segment.name);
+            }
+        });
+
+    let segment = &state.segments[state.selected_segment];
+
+    let segment_times = || {
+        Values::from_values_iter(
+            segment
+                .history
+                .iter()
+                .map(|(index, time)| Value::new(*index, *time)),
+        )
+    };
+
+    let finished_times = || {
+        Values::from_values_iter(
+            state
+                .finished_attempts
+                .iter()
+                .map(|(index, time)| Value::new(*index, *time)),
+        )
+    };
+
+    Plot::new("attempt plot")
+        .height(ui.available_height())
+        .show(ui, |plot_ui| {
+            plot_ui.line(Line::new(segment_times()).name(&segment.name));
+            plot_ui.points(Points::new(segment_times()).name(&segment.name));
+            plot_ui.points(Points::new(finished_times()).name("Finished Runs"));
+        });
+}
@@ -1,10 +1,17 @@
-use enum_map::Enum;
-use livesplit_core::{hotkey::KeyCode, HotkeyConfig};
+use enum_map::{Enum, EnumMap};
+use livesplit_core::{
+    hotkey::{self, Hook, KeyCode},
+    HotkeyConfig, SharedTimer, Timer,
+};
+use serde::{Deserialize, Serialize};
 
-#[derive(Copy, Clone, Enum)]
+use crate::{EventSender, UserEvent};
+
+#[derive(Copy, Clone, Debug, Enum)]
 pub enum Hotkey {
     Split,
     Reset,
+    ResetAndDiscard,
     Undo,
     Skip,
     Pause,
@@ -12,6 +19,10 @@ pub enum Hotkey {
     PreviousComparison,
     NextComparison,
     ToggleTimingMethod,
+    InitializeGameTime,
+    PauseGameTime,
+    ResumeGameTime,
+    TogglePracticeMode,
 }
 
 impl Hotkey {
@@ -19,6 +30,7 @@ impl Hotkey {
         match self {
             Hotkey::Split => "Split",
             Hotkey::Reset => "Reset",
+            Hotkey::ResetAndDiscard => "Reset and Discard",
             Hotkey::Undo => "Undo",
             Hotkey::Skip => "Skip",
             Hotkey::Pause => "Pause",
@@ -26,13 +38,22 @@ impl Hotkey {
             Hotkey::PreviousComparison => "Previous Comparison",
             Hotkey::NextComparison => "Next Comparison",
             Hotkey::ToggleTimingMethod => "Toggle Timing Method",
+            Hotkey::InitializeGameTime => "Initialize Game Time",
+            Hotkey::PauseGameTime => "Pause Game Time",
+            Hotkey::ResumeGameTime => "Resume Game Time",
+            Hotkey::TogglePracticeMode => "Toggle Practice Mode",
         }
     }
 
-    pub fn get_keycode(self, config: &HotkeyConfig) -> Option<KeyCode> {
+    pub fn get_keycode(
+        self,
+        config: &HotkeyConfig,
+        extra_config: &ExtraHotkeyConfig,
+    ) -> Option<KeyCode> {
         match self {
             Hotkey::Split => config.split,
             Hotkey::Reset => config.reset,
+            Hotkey::ResetAndDiscard => extra_config.reset_and_discard,
             Hotkey::Undo => config.undo,
             Hotkey::Skip => config.skip,
             Hotkey::Pause => config.pause,
@@ -40,13 +61,23 @@ impl Hotkey {
             Hotkey::PreviousComparison => config.previous_comparison,
             Hotkey::NextComparison => config.next_comparison,
             Hotkey::ToggleTimingMethod => config.toggle_timing_method,
+            Hotkey::InitializeGameTime => extra_config.initialize_game_time,
+            Hotkey::PauseGameTime => extra_config.pause_game_time,
+            Hotkey::ResumeGameTime => extra_config.resume_game_time,
+            Hotkey::TogglePracticeMode => extra_config.toggle_practice_mode,
         }
     }
 
-    pub fn set_keycode(self, config: &mut HotkeyConfig, keycode: Option<KeyCode>) {
+    pub fn set_keycode(
+        self,
+        config: &mut HotkeyConfig,
+        extra_config: &mut ExtraHotkeyConfig,
+        keycode: Option<KeyCode>,
+    ) {
         match self {
             Hotkey::Split => config.split = keycode,
             Hotkey::Reset => config.reset = keycode,
+            Hotkey::ResetAndDiscard => extra_config.reset_and_discard = keycode,
             Hotkey::Undo => config.undo = keycode,
             Hotkey::Skip => config.skip = keycode,
             Hotkey::Pause => config.pause = keycode,
@@ -54,7 +85,285 @@ impl Hotkey {
             Hotkey::PreviousComparison => config.previous_comparison = keycode,
             Hotkey::NextComparison => config.next_comparison = keycode,
             Hotkey::ToggleTimingMethod => config.toggle_timing_method = keycode,
+            Hotkey::InitializeGameTime => extra_config.initialize_game_time = keycode,
+            Hotkey::PauseGameTime => extra_config.pause_game_time = keycode,
+            Hotkey::ResumeGameTime => extra_config.resume_game_time = keycode,
+            Hotkey::TogglePracticeMode => extra_config.toggle_practice_mode = keycode,
+        };
+    }
+
+    /// Whether the hotkey needs more than the timer, these go through `SharedState::run_hotkey`.
+    pub fn needs_shared_state(self) -> bool {
+        matches!(
+            self,
+            Hotkey::Reset | Hotkey::ResetAndDiscard | Hotkey::TogglePracticeMode
+        )
+    }
+
+    /// Does whatever pressing the hotkey would do to the timer, for the hotkeys that don't need
+    /// the shared state. Outside the crate hotkeys run through `SharedState::run_hotkey`.
+    pub(crate) fn execute(self, timer: &mut Timer) {
+        match self {
+            Hotkey::Split => timer.split_or_start(),
+            Hotkey::Reset | Hotkey::ResetAndDiscard | Hotkey::TogglePracticeMode => {
+                unreachable!("{} needs the shared state", self.get_name())
+            }
+            Hotkey::Undo => timer.undo_split(),
+            Hotkey::Skip => timer.skip_split(),
+            Hotkey::Pause => timer.toggle_pause_or_start(),
+            Hotkey::UndoAllPauses => timer.undo_all_pauses(),
+            Hotkey::PreviousComparison => timer.switch_to_previous_comparison(),
+            Hotkey::NextComparison => timer.switch_to_next_comparison(),
+            Hotkey::ToggleTimingMethod => timer.toggle_timing_method(),
+            Hotkey::InitializeGameTime => timer.initialize_game_time(),
+            Hotkey::PauseGameTime => timer.pause_game_time(),
+            Hotkey::ResumeGameTime => timer.resume_game_time(),
+        }
+    }
+}
+
+/// Keys for the hotkeys livesplit's `HotkeyConfig` doesn't have.
+#[derive(Copy, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExtraHotkeyConfig {
+    pub reset_and_discard: Option<KeyCode>,
+    pub initialize_game_time: Option<KeyCode>,
+    pub pause_game_time: Option<KeyCode>,
+    pub resume_game_time: Option<KeyCode>,
+    pub toggle_practice_mode: Option<KeyCode>,
+}
+
+fn keycodes(
+    config: &HotkeyConfig,
+    extra_config: &ExtraHotkeyConfig,
+) -> EnumMap<Hotkey, Option<KeyCode>> {
+    let mut keycodes: EnumMap<Hotkey, Option<KeyCode>> = Default::default();
+    for (hotkey, keycode) in keycodes.iter_mut() {
+        *keycode = hotkey.get_keycode(config, extra_config);
+    }
+    keycodes
+}
+
+// the hotkey the key is for, if any
+fn hotkey_for(
+    keycode: KeyCode,
+    config: &HotkeyConfig,
+    extra_config: &ExtraHotkeyConfig,
+) -> Option<Hotkey> {
+    keycodes(config, extra_config)
+        .into_iter()
+        .find(|&(_, hotkey_keycode)| hotkey_keycode == Some(keycode))
+        .map(|(hotkey, _)| hotkey)
+}
+
+// what happens when a hotkey's key is pressed, the hotkeys that need the shared state go through
+// the event loop
+fn press(hotkey: Hotkey, timer: &SharedTimer, send_event: &EventSender) {
+    if hotkey.needs_shared_state() {
+        send_event.send_event(UserEvent::Hotkey(hotkey)).ok();
+    } else {
+        hotkey.execute(&mut timer.write());
+    }
+}
+
+// a key can only do one thing, so two hotkeys on the same key can't be registered
+fn check_for_duplicates(
+    config: &HotkeyConfig,
+    extra_config: &ExtraHotkeyConfig,
+) -> hotkey::Result<()> {
+    let mut keys: Vec<KeyCode> = keycodes(config, extra_config)
+        .values()
+        .flatten()
+        .copied()
+        .collect();
+    let count = keys.len();
+
+    keys.sort_unstable_by_key(|keycode| keycode.as_str());
+    keys.dedup();
+
+    if keys.len() != count {
+        return Err(hotkey::Error::AlreadyRegistered);
+    }
+
+    Ok(())
+}
+
+/// Where the hotkeys are registered, so the rest of the state doesn't care whether they are
+/// actually listening to the keyboard.
+pub trait HotkeyBackend {
+    fn with_config(
+        timer: SharedTimer,
+        send_event: EventSender,
+        config: HotkeyConfig,
+        extra_config: ExtraHotkeyConfig,
+    ) -> hotkey::Result<Self>
+    where
+        Self: Sized;
+
+    fn config(&self) -> HotkeyConfig;
+    fn extra_config(&self) -> ExtraHotkeyConfig;
+
+    /// Does what pressing the key does, whether anything listens to the keyboard or not.
+    fn press(&self, keycode: KeyCode);
+
+    /// Sets both configs at once. If either of them can't be used, neither is and the old ones
+    /// stay.
+    fn set_config(
+        &mut self,
+        config: HotkeyConfig,
+        extra_config: ExtraHotkeyConfig,
+    ) -> hotkey::Result<()>;
+}
+
+/// The hotkeys of the running timer, registered with the operating system. Works like livesplit's
+/// `HotkeySystem`, but for every `Hotkey`, the ones that need the shared state are sent to the
+/// event loop.
+pub struct SystemHotkeys {
+    hook: Hook,
+    timer: SharedTimer,
+    send_event: EventSender,
+    registered: Vec<KeyCode>,
+    config: HotkeyConfig,
+    extra_config: ExtraHotkeyConfig,
+}
+
+impl SystemHotkeys {
+    fn register(
+        &mut self,
+        config: HotkeyConfig,
+        extra_config: ExtraHotkeyConfig,
+    ) -> hotkey::Result<()> {
+        check_for_duplicates(&config, &extra_config)?;
+
+        let (old_config, old_extra_config) = (self.config, self.extra_config);
+
+        if let Err(e) = self.register_keys(config, extra_config) {
+            // e.g. another program has one of the keys, the old ones should still work then
+            self.register_keys(old_config, old_extra_config).ok();
+            return Err(e);
+        }
+
+        Ok(())
+    }
+
+    fn register_keys(
+        &mut self,
+        config: HotkeyConfig,
+        extra_config: ExtraHotkeyConfig,
+    ) -> hotkey::Result<()> {
+        while let Some(keycode) = self.registered.pop() {
+            self.hook.unregister(keycode)?;
+        }
+
+        for (hotkey, keycode) in keycodes(&config, &extra_config) {
+            if let Some(keycode) = keycode {
+                let timer = self.timer.clone();
+                let send_event = self.send_event.clone();
+                self.hook
+                    .register(keycode, move || press(hotkey, &timer, &send_event))?;
+                self.registered.push(keycode);
+            }
+        }
+
+        self.config = config;
+        self.extra_config = extra_config;
+
+        Ok(())
+    }
+}
+
+impl HotkeyBackend for SystemHotkeys {
+    fn with_config(
+        timer: SharedTimer,
+        send_event: EventSender,
+        config: HotkeyConfig,
+        extra_config: ExtraHotkeyConfig,
+    ) -> hotkey::Result<Self> {
+        let mut hotkeys = Self {
+            hook: Hook::new()?,
+            timer,
+            send_event,
+            registered: Vec::new(),
+            config,
+            extra_config,
         };
+
+        hotkeys.register(config, extra_config)?;
+
+        Ok(hotkeys)
+    }
+
+    fn config(&self) -> HotkeyConfig {
+        self.config
+    }
+
+    fn extra_config(&self) -> ExtraHotkeyConfig {
+        self.extra_config
+    }
+
+    fn press(&self, keycode: KeyCode) {
+        if let Some(hotkey) = hotkey_for(keycode, &self.config, &self.extra_config) {
+            press(hotkey, &self.timer, &self.send_event);
+        }
+    }
+
+    fn set_config(
+        &mut self,
+        config: HotkeyConfig,
+        extra_config: ExtraHotkeyConfig,
+    ) -> hotkey::Result<()> {
+        self.register(config, extra_config)
+    }
+}
+
+/// Only remembers the keys, nothing is listening for them, but they can be pressed with
+/// `HotkeyBackend::press`. For tests and machines without a keyboard hook.
+pub struct MemoryHotkeys {
+    timer: SharedTimer,
+    send_event: EventSender,
+    config: HotkeyConfig,
+    extra_config: ExtraHotkeyConfig,
+}
+
+impl HotkeyBackend for MemoryHotkeys {
+    fn with_config(
+        timer: SharedTimer,
+        send_event: EventSender,
+        config: HotkeyConfig,
+        extra_config: ExtraHotkeyConfig,
+    ) -> hotkey::Result<Self> {
+        Ok(Self {
+            timer,
+            send_event,
+            config,
+            extra_config,
+        })
+    }
+
+    fn config(&self) -> HotkeyConfig {
+        self.config
+    }
+
+    fn extra_config(&self) -> ExtraHotkeyConfig {
+        self.extra_config
+    }
+
+    fn press(&self, keycode: KeyCode) {
+        if let Some(hotkey) = hotkey_for(keycode, &self.config, &self.extra_config) {
+            press(hotkey, &self.timer, &self.send_event);
+        }
+    }
+
+    fn set_config(
+        &mut self,
+        config: HotkeyConfig,
+        extra_config: ExtraHotkeyConfig,
+    ) -> hotkey::Result<()> {
+        check_for_duplicates(&config, &extra_config)?;
+
+        self.config = config;
+        self.extra_config = extra_config;
+        Ok(())
     }
 }
 
@@ -0,0 +1,190 @@
+use std::{
+    fs::{self, DirBuilder},
+    io::{self, BufRead, BufReader, ErrorKind, Write},
+    os::unix::{
+        fs::{DirBuilderExt, MetadataExt},
+        net::{UnixListener, UnixStream},
+    },
+    path::Path,
+    sync::mpsc::{self, Sender},
+    thread,
+};
+
+use livesplit_core::Timer;
+
+use crate::{event_sink::EventSender, utils::format_time, UserEvent};
+
+mod path;
+pub use path::socket_path;
+
+/// Where the event loop sends the response to an `IpcCommand`.
+pub type IpcResponder = Sender<String>;
+
+/// A command sent by `lsod-ctl`.
+pub enum IpcCommand {
+    Start,
+    Split,
+    Undo,
+    Skip,
+    Pause,
+    Reset,
+    NextComparison,
+    PreviousComparison,
+    SetComparison(String),
+    CurrentTime,
+}
+
+impl IpcCommand {
+    pub fn parse(line: &str) -> Result<IpcCommand, String> {
+        let mut words = line.split_whitespace();
+
+        let command = match words.next() {
+            Some(command) => command,
+            None => return Err("no command given".to_owned()),
+        };
+
+        Ok(match command {
+            "start" => IpcCommand::Start,
+            "split" => IpcCommand::Split,
+            "undo" => IpcCommand::Undo,
+            "skip" => IpcCommand::Skip,
+            "pause" => IpcCommand::Pause,
+            "reset" => IpcCommand::Reset,
+            "comparison" => match words.next() {
+                Some("next") => IpcCommand::NextComparison,
+                Some("previous") => IpcCommand::PreviousComparison,
+                Some(_) => {
+                    // comparison names may contain spaces
+                    let (_, name) = line.trim().split_once(char::is_whitespace).unwrap();
+                    IpcCommand::SetComparison(name.trim().to_owned())
+                }
+                None => return Err("comparison needs next, previous or a name".to_owned()),
+            },
+            "time" => IpcCommand::CurrentTime,
+            _ => return Err(format!("unknown command {command}")),
+        })
+    }
+
+    /// Runs the command, returning what should be sent back to `lsod-ctl`. Resets need more than
+    /// the timer, outside the crate commands run through `SharedState::run_ipc_command`.
+    pub(crate) fn execute(self, timer: &mut Timer) -> String {
+        match self {
+            IpcCommand::Start => timer.start(),
+            IpcCommand::Split => timer.split(),
+            IpcCommand::Undo => timer.undo_split(),
+            IpcCommand::Skip => timer.skip_split(),
+            IpcCommand::Pause => timer.toggle_pause(),
+            IpcCommand::Reset => unreachable!("resets go through the shared state"),
+            IpcCommand::NextComparison => timer.switch_to_next_comparison(),
+            IpcCommand::PreviousComparison => timer.switch_to_previous_comparison(),
+            IpcCommand::SetComparison(name) => {
+                if timer.set_current_comparison(name).is_err() {
+                    return "error: no such comparison".to_owned();
+                }
+            }
+            IpcCommand::CurrentTime => {
+                let method = timer.current_timing_method();
+                return format_time(timer.snapshot().current_time()[method]);
+            }
+        }
+
+        "ok".to_owned()
+    }
+}
+
+/// Listens on the unix socket at `path` for commands from `lsod-ctl` and forwards them to the
+/// event loop. The socket's directory has to be one only the user can enter, it's created if it
+/// doesn't exist.
+pub fn start(path: &Path, send_event: EventSender) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        create_private_dir(dir)?;
+    }
+
+    if path.exists() {
+        // another timer is still listening, taking its socket would leave lsod-ctl talking to us
+        if UnixStream::connect(path).is_ok() {
+            return Err(io::Error::new(
+                ErrorKind::AddrInUse,
+                format!("another timer is already listening on {}", path.display()),
+            ));
+        }
+
+        // a socket left over from a previous run would make binding fail
+        fs::remove_file(path)?;
+    }
+
+    // anyone who can connect can control the timer, so the socket is only the user's from the
+    // moment it exists
+    let umask = unsafe { libc::umask(0o177) };
+    let listener = UnixListener::bind(path);
+    unsafe { libc::umask(umask) };
+    let listener = listener?;
+
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) => {
+                    let send_event = send_event.clone();
+
+                    thread::spawn(move || {
+                        handle_connection(stream, send_event)
+                            .unwrap_or_else(|e| eprintln!("IPC connection failed, got error {e}"))
+                    });
+                }
+                Err(e) => eprintln!("Failed to accept IPC connection, got error {e}"),
+            }
+        }
+    });
+
+    Ok(())
+}
+
+// the directory of the socket has to be only for the user, whoever created it. someone else's
+// directory in a shared place like /tmp could let them swap the socket for their own
+fn create_private_dir(dir: &Path) -> io::Result<()> {
+    DirBuilder::new().recursive(true).mode(0o700).create(dir)?;
+
+    let metadata = fs::metadata(dir)?;
+    let uid = unsafe { libc::getuid() };
+
+    if !metadata.is_dir() || metadata.uid() != uid || metadata.mode() & 0o777 != 0o700 {
+        return Err(io::Error::new(
+            ErrorKind::PermissionDenied,
+            format!(
+                "{} has to be a directory only you can access",
+                dir.display()
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
+fn handle_connection(stream: UnixStream, send_event: EventSender) -> io::Result<()> {
+    let mut writer = stream.try_clone()?;
+
+    for line in BufReader::new(stream).lines() {
+        let response = match IpcCommand::parse(&line?) {
+            Ok(command) => {
+                let (sender, receiver) = mpsc::channel();
+
+                if send_event
+                    .send_event(UserEvent::IpcCommand(command, sender))
+                    .is_err()
+                {
+                    // the event loop is gone, so we are shutting down
+                    return Ok(());
+                }
+
+                receiver
+                    .recv()
+                    .unwrap_or_else(|_| "error: the timer didn't respond".to_owned())
+            }
+            Err(e) => format!("error: {e}"),
+        };
+
+        writeln!(writer, "{response}")?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,18 @@
+use std::{env, path::PathBuf};
+
+/// Where the timer listens for commands. Can be overridden with the `LSOD_SOCKET` environment
+/// variable.
+pub fn socket_path() -> PathBuf {
+    if let Some(path) = env::var_os("LSOD_SOCKET") {
+        return path.into();
+    }
+
+    let mut path = dirs::runtime_dir().unwrap_or_else(|| {
+        // the temporary directory is shared, so the socket goes into a directory of the user's
+        // own, which the timer creates only they can enter
+        let uid = unsafe { libc::getuid() };
+        env::temp_dir().join(format!("lsod-{uid}"))
+    });
+    path.push("lsod.sock");
+    path
+}
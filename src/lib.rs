@@ -0,0 +1,372 @@
+use configuration::Configuration;
+use glutin::{
+    dpi::PhysicalPosition,
+    event::{Event, WindowEvent},
+    event_loop::{ControlFlow, EventLoop},
+    window::WindowId,
+};
+
+use livesplit_core::{
+    parking_lot::RwLock, run::saver::livesplit::save_run, Layout, TimeSpan, Timer, TimerPhase,
+};
+use rfd::{MessageButtons, MessageDialog};
+use std::{collections::HashMap, env, fs::File, io::BufWriter, process, sync::Arc};
+
+use crate::{
+    accessibility::Announcer, auto_splitter::AutoSplitter, context_menu::ContextMenu,
+    gui::ConfigurationWindow, hotkey::SystemHotkeys, main_window::MainWindow,
+    websocket::WebSocketServer, window::ApplicationWindow,
+};
+
+mod accessibility;
+mod auto_splitter;
+pub mod configuration;
+mod context_menu;
+mod event_sink;
+pub mod gui;
+mod hotkey;
+#[cfg(unix)]
+mod ipc;
+mod main_window;
+mod practice;
+mod render;
+mod reset;
+mod run_edits;
+mod server;
+mod utils;
+mod websocket;
+mod window;
+
+pub use event_sink::{EventQueue, EventSender, EventSink};
+pub use hotkey::{Hotkey, HotkeyBackend, MemoryHotkeys};
+#[cfg(unix)]
+pub use ipc::{start as start_ipc, IpcCommand, IpcResponder};
+pub use reset::ResetPolicy;
+pub use run_edits::RunEdit;
+pub use server::{Command, ResponseSender, Server, ServerConfig};
+
+pub enum UserEvent {
+    SpawnConfigurationWindow,
+    SpawnContextMenu(PhysicalPosition<i32>),
+    Resize,
+    DestroyWindow(WindowId),
+    Hotkey(Hotkey),
+    ServerCommand(Command, ResponseSender),
+    #[cfg(unix)]
+    IpcCommand(ipc::IpcCommand, ipc::IpcResponder),
+}
+
+pub struct SharedState {
+    layout: Layout,
+    timer: Arc<RwLock<Timer>>,
+    hotkeys: Box<dyn HotkeyBackend>,
+    auto_splitter: AutoSplitter,
+    server: Option<Server>,
+    websocket_server: Option<WebSocketServer>,
+    config: Configuration,
+    send_event: EventSender,
+    has_configuration_window: bool,
+    // attempts aren't recorded in the splits
+    practice_mode: bool,
+    // the run's offset from before starting from a later segment
+    offset_to_restore: Option<TimeSpan>,
+    // whether practice mode was on before starting from a later segment turned it on
+    practice_mode_to_restore: Option<bool>,
+    // whether the attempt that reached the end goes into the splits, once that's decided
+    ended_attempt: Option<bool>,
+    // edits to the splits made during the attempt, made on reset
+    queued_edits: Vec<RunEdit>,
+    // whether the icons of the queued edits were sent to the layout state
+    queued_icons_shown: bool,
+    announcer: Announcer,
+}
+
+impl SharedState {
+    pub fn new(config: Configuration, event_loop: &EventLoop<UserEvent>) -> SharedState {
+        SharedState::with_backends::<SystemHotkeys>(config, Box::new(event_loop.create_proxy()))
+    }
+
+    /// Builds the state without touching the event loop or the keyboard, e.g. with an
+    /// `EventQueue` and `MemoryHotkeys` in tests.
+    pub fn with_backends<H: HotkeyBackend + 'static>(
+        config: Configuration,
+        send_event: EventSender,
+    ) -> SharedState {
+        let timer: Arc<RwLock<Timer>> = Timer::new(config.run_or_default()).unwrap().into_shared();
+        let hotkeys = H::with_config(
+            timer.clone(),
+            send_event.clone(),
+            config.profile().hotkey_config,
+            config.profile().extra_hotkey_config,
+        )
+        .unwrap_or_else(|e| panic!("Could not initialize hotkey system, got error {e}"));
+
+        let mut auto_splitter = AutoSplitter::new(&timer.read());
+        auto_splitter.load_for_splits(&config);
+
+        let server = Server::from_config(&config.server, send_event.clone());
+        let websocket_server = WebSocketServer::from_config(&config.websocket);
+
+        SharedState {
+            layout: config.layout_or_default(),
+            timer,
+            config,
+            hotkeys: Box::new(hotkeys),
+            auto_splitter,
+            server,
+            websocket_server,
+            has_configuration_window: false,
+            send_event,
+            practice_mode: false,
+            offset_to_restore: None,
+            practice_mode_to_restore: None,
+            ended_attempt: None,
+            queued_edits: Vec::new(),
+            queued_icons_shown: true,
+            announcer: Announcer::default(),
+        }
+    }
+
+    pub fn timer(&self) -> &Arc<RwLock<Timer>> {
+        &self.timer
+    }
+
+    pub fn config(&self) -> &Configuration {
+        &self.config
+    }
+
+    pub fn hotkeys(&self) -> &dyn HotkeyBackend {
+        self.hotkeys.as_ref()
+    }
+
+    pub fn server(&self) -> Option<&Server> {
+        self.server.as_ref()
+    }
+
+    pub fn practice_mode(&self) -> bool {
+        self.practice_mode
+    }
+
+    /// Does whatever pressing the hotkey would do.
+    pub fn run_hotkey(&mut self, hotkey: Hotkey) {
+        match hotkey {
+            Hotkey::Reset => self.reset(),
+            Hotkey::ResetAndDiscard => self.reset_and_discard(),
+            Hotkey::TogglePracticeMode => self
+                .toggle_practice_mode()
+                .unwrap_or_else(|e| eprintln!("Failed to toggle practice mode, got error {e}")),
+            hotkey => hotkey.execute(&mut self.timer.write()),
+        }
+    }
+
+    /// Runs a command from a LiveSplit Server client, returning the response if it has one.
+    pub fn run_command(&mut self, command: Command) -> Option<String> {
+        match command {
+            // resets have to know about practice mode
+            Command::Reset => {
+                self.reset();
+                None
+            }
+            command => command.execute(&mut self.timer.write()),
+        }
+    }
+
+    /// Runs a command from `lsod-ctl`, returning the response.
+    #[cfg(unix)]
+    pub fn run_ipc_command(&mut self, command: ipc::IpcCommand) -> String {
+        match command {
+            // resets have to know about practice mode
+            ipc::IpcCommand::Reset => {
+                self.reset();
+                "ok".to_owned()
+            }
+            command => command.execute(&mut self.timer.write()),
+        }
+    }
+
+    /// Practice mode can only change between attempts, an attempt is either recorded or not.
+    pub fn toggle_practice_mode(&mut self) -> Result<(), String> {
+        if self.timer.read().current_phase() != TimerPhase::NotRunning {
+            return Err("You can't toggle practice mode during an attempt".to_owned());
+        }
+
+        self.practice_mode = !self.practice_mode;
+        Ok(())
+    }
+
+    /// Makes another profile the current one and loads its splits, layout, hotkeys and size.
+    pub fn switch_profile(&mut self, index: usize) -> Result<(), String> {
+        if index >= self.config.profiles.len() {
+            return Err(format!("There is no profile {index}"));
+        }
+
+        if index == self.config.current_profile {
+            return Ok(());
+        }
+
+        if let TimerPhase::Running | TimerPhase::Paused = self.timer.read().current_phase() {
+            return Err("You can't switch profiles while the timer is running".to_owned());
+        }
+
+        self.finish_attempt();
+
+        let mut timer = self.timer.write();
+
+        let should_save_splits = timer.run().has_been_modified()
+            && MessageDialog::new()
+                .set_buttons(MessageButtons::YesNo)
+                .set_title("Save Splits?")
+                .set_description("Your splits have been edited, would you like to save them now?")
+                .show();
+
+        self.config.current_profile = index;
+
+        let old_splits = timer.replace_run(self.config.run_or_default(), true);
+
+        if should_save_splits {
+            if let Ok(splits) = old_splits {
+                if let Some(path) = splits.path() {
+                    if let Ok(file) = File::create(path) {
+                        save_run(&splits, BufWriter::new(file)).ok();
+                    }
+                }
+            }
+        }
+
+        drop(timer);
+
+        self.layout = self.config.layout_or_default();
+        self.auto_splitter.load_for_splits(&self.config);
+        self.send_event.send_event(UserEvent::Resize).ok();
+
+        let profile = self.config.profile();
+
+        self.hotkeys
+            .set_config(profile.hotkey_config, profile.extra_hotkey_config)
+            .map_err(|e| format!("Could not set the hotkeys of the profile, got error {e}"))
+    }
+}
+
+/// Runs the timer, or the subcommand given on the command line.
+pub fn run() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    if args.first().map(String::as_str) == Some("render") {
+        if let Err(e) = render::render_command(&args[1..]) {
+            eprintln!("{e:#}");
+            process::exit(1);
+        }
+
+        return;
+    }
+
+    let mut config = Configuration::get_or_default();
+    let event_loop = EventLoop::with_user_event();
+
+    // the timer was most likely on the primary monitor when an older version saved its size
+    let scale_factor = event_loop
+        .primary_monitor()
+        .map_or(1., |monitor| monitor.scale_factor());
+    config.convert_physical_sizes(scale_factor);
+
+    let mut main_window = MainWindow::new(&event_loop, config.profile().window_size());
+    let main_window_id = main_window.id();
+
+    let mut other_windows: HashMap<WindowId, Box<dyn ApplicationWindow>> = HashMap::new();
+    // the last context menu, it might already be closed
+    let mut context_menu_id: Option<WindowId> = None;
+
+    let mut shared_state = SharedState::new(config, &event_loop);
+
+    #[cfg(unix)]
+    ipc::start(&ipc::socket_path(), Box::new(event_loop.create_proxy()))
+        .unwrap_or_else(|e| eprintln!("Failed to start IPC server, got error {e}"));
+
+    event_loop.run(move |event, event_loop, control_flow| {
+        *control_flow = ControlFlow::Poll;
+
+        match event {
+            Event::WindowEvent {
+                window_id,
+                event: WindowEvent::CloseRequested | WindowEvent::Destroyed,
+            }
+            | Event::UserEvent(UserEvent::DestroyWindow(window_id)) => {
+                if window_id == main_window_id {
+                    if main_window.on_destroy(&mut shared_state) {
+                        other_windows.clear();
+                        *control_flow = ControlFlow::Exit;
+                    }
+                } else if let Some(window) = other_windows.get_mut(&window_id) {
+                    if window.on_destroy(&mut shared_state) {
+                        other_windows.remove(&window_id);
+                    }
+                }
+            }
+
+            Event::WindowEvent { window_id, event } => {
+                let window = if window_id == main_window_id {
+                    &mut main_window
+                } else if let Some(window) = other_windows.get_mut(&window_id) {
+                    window.as_mut()
+                } else {
+                    return;
+                };
+
+                window.window_event(event, &mut shared_state);
+            }
+
+            Event::RedrawRequested(window_id) => {
+                let window = if window_id == main_window_id {
+                    &mut main_window
+                } else if let Some(window) = other_windows.get_mut(&window_id) {
+                    window.as_mut()
+                } else {
+                    return;
+                };
+
+                window.redraw(&mut shared_state);
+            }
+
+            Event::MainEventsCleared => {
+                shared_state.update_auto_splitter();
+                shared_state.check_for_end();
+                shared_state.check_for_announcements();
+                main_window.redraw(&mut shared_state);
+            }
+            Event::UserEvent(UserEvent::SpawnConfigurationWindow) => {
+                let configuration_window =
+                    ConfigurationWindow::new(event_loop, &shared_state.config.appearance);
+                other_windows.insert(configuration_window.id(), Box::new(configuration_window));
+            }
+            Event::UserEvent(UserEvent::SpawnContextMenu(position)) => {
+                // only one menu at a time, right-clicking again moves it to the cursor
+                if let Some(id) = context_menu_id.take() {
+                    other_windows.remove(&id);
+                }
+
+                let context_menu = ContextMenu::new(event_loop, position);
+                context_menu_id = Some(context_menu.id());
+                other_windows.insert(context_menu.id(), Box::new(context_menu));
+            }
+
+            Event::UserEvent(UserEvent::Resize) => {
+                main_window.resize(shared_state.config.profile().window_size());
+            }
+
+            Event::UserEvent(UserEvent::Hotkey(hotkey)) => {
+                shared_state.run_hotkey(hotkey);
+            }
+
+            Event::UserEvent(UserEvent::ServerCommand(command, response)) => {
+                let response_text = shared_state.run_command(command);
+                response.send(response_text).ok();
+            }
+
+            #[cfg(unix)]
+            Event::UserEvent(UserEvent::IpcCommand(command, response)) => {
+                response.send(shared_state.run_ipc_command(command)).ok();
+            }
+            _ => {}
+        }
+    })
+}
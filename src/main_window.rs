@@ -1,7 +1,7 @@
 use std::{fs::File, io::BufWriter};
 
 use glutin::{
-    dpi::PhysicalSize,
+    dpi::{LogicalSize, PhysicalPosition, PhysicalSize},
     event::{ElementState, MouseButton, MouseScrollDelta, WindowEvent},
     event_loop::EventLoop,
     window::{Window, WindowBuilder, WindowId},
@@ -13,6 +13,8 @@ use livesplit_core::{
 use pixels::{Pixels, SurfaceTexture};
 use rfd::{MessageButtons, MessageDialog};
 
+#[cfg(target_os = "linux")]
+use crate::accessibility::AccessibilityTree;
 use crate::{window::ApplicationWindow, SharedState, UserEvent};
 
 pub struct MainWindow {
@@ -21,6 +23,15 @@ pub struct MainWindow {
     renderer: BorrowedRenderer,
     layout_state: LayoutState,
     window_size: PhysicalSize<u32>,
+    // what the window size should stay at when the scale factor changes
+    logical_size: LogicalSize<f64>,
+    cursor_position: PhysicalPosition<f64>,
+    // whether the practice mode border is drawn
+    practice_mode: bool,
+    // what the title says, so it's only set when it changes
+    title: String,
+    #[cfg(target_os = "linux")]
+    accessibility_tree: AccessibilityTree,
 }
 
 impl ApplicationWindow for MainWindow {
@@ -31,21 +42,39 @@ impl ApplicationWindow for MainWindow {
                 button: MouseButton::Right,
                 ..
             } => {
-                if !shared_state.has_configuration_window {
-                    shared_state.has_configuration_window = true;
-                    shared_state
-                        .send_event
-                        .send_event(UserEvent::SpawnConfigurationWindow)
-                        .ok();
-                }
+                // the context menu wants a position on the screen, not in our window
+                let window_position = self.window.inner_position().unwrap_or_default();
+                let position = PhysicalPosition::new(
+                    window_position.x + self.cursor_position.x as i32,
+                    window_position.y + self.cursor_position.y as i32,
+                );
+
+                shared_state
+                    .send_event
+                    .send_event(UserEvent::SpawnContextMenu(position))
+                    .ok();
+            }
+            WindowEvent::CursorMoved { position, .. } => {
+                self.cursor_position = position;
             }
             WindowEvent::Resized(size) => {
-                self.pixels.resize_surface(size.width, size.height);
-                self.pixels.resize_buffer(size.width, size.height);
-                self.window_size = size;
+                self.resize_buffer(size);
 
-                self.request_redraw();
+                // e.g. the user dragged the window bigger, but not minimized it
+                if size.width != 0 && size.height != 0 {
+                    self.logical_size = size.to_logical(self.window.scale_factor());
+                }
             }
+            WindowEvent::ScaleFactorChanged {
+                scale_factor,
+                new_inner_size,
+            } => {
+                // moved to a monitor with another scale factor, the timer should look as big there
+                *new_inner_size = self.logical_size.to_physical(scale_factor);
+                self.resize_buffer(*new_inner_size);
+            }
+            #[cfg(target_os = "linux")]
+            WindowEvent::Focused(focused) => self.accessibility_tree.set_focused(focused),
             WindowEvent::MouseWheel { delta, .. } => match delta {
                 MouseScrollDelta::LineDelta(_, vert) => {
                     if vert > 0.0 {
@@ -68,24 +97,54 @@ impl ApplicationWindow for MainWindow {
 
     fn redraw(&mut self, shared_state: &mut crate::SharedState) {
         if self.window_size.width != 0 && self.window_size.height != 0 {
-            let timer = shared_state.timer.read();
+            shared_state.layout.update_state(
+                &mut self.layout_state,
+                &shared_state.timer.read().snapshot(),
+            );
+            shared_state.show_queued_edits(&mut self.layout_state);
 
-            shared_state
-                .layout
-                .update_state(&mut self.layout_state, &timer.snapshot());
+            // the border has to be painted over when practice mode is turned off
+            let force_redraw = self.practice_mode != shared_state.practice_mode;
+            self.practice_mode = shared_state.practice_mode;
 
             self.renderer.render(
                 &self.layout_state,
                 self.pixels.get_frame(),
                 [self.window_size.width, self.window_size.height],
                 self.window_size.width,
-                false,
+                force_redraw,
             );
 
+            if self.practice_mode {
+                draw_practice_border(self.pixels.get_frame(), self.window_size);
+            }
+
             self.pixels
                 .render()
                 .unwrap_or_else(|e| panic!("Pixels failed to redraw, got error {e}"));
         }
+
+        let title = if shared_state.config.accessibility.describe_in_title {
+            shared_state.describe_timer()
+        } else {
+            TITLE.to_owned()
+        };
+
+        if title != self.title {
+            self.window.set_title(&title);
+            self.title = title;
+        }
+
+        #[cfg(target_os = "linux")]
+        self.accessibility_tree.update(shared_state);
+
+        // commands from the clients need the whole shared state, e.g. resets in practice mode
+        if let Some(websocket_server) = shared_state.websocket_server.take() {
+            websocket_server.update(&self.layout_state, |command| {
+                shared_state.run_command(command)
+            });
+            shared_state.websocket_server = Some(websocket_server);
+        }
     }
 
     fn id(&self) -> WindowId {
@@ -97,7 +156,10 @@ impl ApplicationWindow for MainWindow {
     }
 
     fn on_destroy(&mut self, shared_state: &mut SharedState) -> bool {
-        shared_state.config.save().ok();
+        shared_state
+            .config
+            .save()
+            .unwrap_or_else(|e| eprintln!("Failed to save configuration, got error {e:#}"));
 
         let mut timer = shared_state.timer.write();
 
@@ -113,6 +175,14 @@ impl ApplicationWindow for MainWindow {
             }
         }
 
+        // an attempt that reached the end is already saved, this puts it into the run the same way.
+        // practice attempts are thrown away, they might have changed the offset
+        if shared_state.practice_mode || timer.current_phase() == TimerPhase::Ended {
+            drop(timer);
+            shared_state.finish_attempt();
+            timer = shared_state.timer.write();
+        }
+
         let should_save_splits = if timer.run().has_been_modified() {
             MessageDialog::new()
                 .set_buttons(MessageButtons::YesNo)
@@ -124,11 +194,6 @@ impl ApplicationWindow for MainWindow {
         };
 
         if should_save_splits {
-            // for some reason when the timer is in the "ended" state, the new pb doesn't save
-            if let TimerPhase::Ended = timer.current_phase() {
-                timer.reset(true);
-            }
-
             let splits = timer.run();
             if let Some(path) = splits.path() {
                 if let Ok(file) = File::create(path) {
@@ -141,10 +206,10 @@ impl ApplicationWindow for MainWindow {
     }
 }
 impl MainWindow {
-    pub(crate) fn new(event_loop: &EventLoop<UserEvent>, size: (u32, u32)) -> Self {
-        println!("{}, {}", size.0, size.1);
+    pub(crate) fn new(event_loop: &EventLoop<UserEvent>, size: LogicalSize<f64>) -> Self {
         let window = WindowBuilder::new()
-            .with_inner_size(PhysicalSize::new(size.0, size.1))
+            .with_title(TITLE)
+            .with_inner_size(size)
             .build(event_loop)
             .unwrap_or_else(|e| panic!("Could not create main window, got error {e}"));
 
@@ -161,14 +226,50 @@ impl MainWindow {
         Self {
             window,
             window_size,
+            logical_size: size,
             pixels,
             renderer,
             layout_state: LayoutState::default(),
+            cursor_position: PhysicalPosition::default(),
+            practice_mode: false,
+            title: TITLE.to_owned(),
+            #[cfg(target_os = "linux")]
+            accessibility_tree: AccessibilityTree::new(TITLE, true),
         }
     }
 
-    pub fn resize(&mut self, size: (u32, u32)) {
-        self.window
-            .set_inner_size(PhysicalSize::new(size.0, size.1));
+    pub fn resize(&mut self, size: LogicalSize<f64>) {
+        self.logical_size = size;
+        self.window.set_inner_size(size);
+    }
+
+    // the renderer draws in physical pixels, so the layout fills the window at any scale factor
+    fn resize_buffer(&mut self, size: PhysicalSize<u32>) {
+        self.pixels.resize_surface(size.width, size.height);
+        self.pixels.resize_buffer(size.width, size.height);
+        self.window_size = size;
+
+        self.request_redraw();
+    }
+}
+
+const TITLE: &str = "LiveSplit One";
+
+const PRACTICE_BORDER_WIDTH: u32 = 3;
+const PRACTICE_BORDER_COLOR: [u8; 4] = [0xff, 0x99, 0x00, 0xff];
+
+// an orange frame around the timer, so nobody forgets their attempts aren't recorded
+fn draw_practice_border(frame: &mut [u8], size: PhysicalSize<u32>) {
+    for (i, pixel) in frame.chunks_exact_mut(4).enumerate() {
+        let x = i as u32 % size.width;
+        let y = i as u32 / size.width;
+
+        if x < PRACTICE_BORDER_WIDTH
+            || y < PRACTICE_BORDER_WIDTH
+            || x >= size.width.saturating_sub(PRACTICE_BORDER_WIDTH)
+            || y >= size.height.saturating_sub(PRACTICE_BORDER_WIDTH)
+        {
+            pixel.copy_from_slice(&PRACTICE_BORDER_COLOR);
+        }
     }
 }
@@ -0,0 +1,102 @@
+use std::{
+    fs::OpenOptions,
+    io::Write,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Context, Result};
+use livesplit_core::{TimeSpan, Timer, TimerPhase};
+use rfd::{MessageDialog, MessageLevel};
+
+use crate::{configuration::Configuration, utils::format_time, SharedState};
+
+impl SharedState {
+    /// Starts the timer at the personal best split time of the segment before `index`, with the
+    /// splits before it skipped, so the comparisons mean something for the practiced segment.
+    /// Such an attempt can't be recorded, so this turns on practice mode until the reset.
+    pub fn start_from_segment(&mut self, index: usize) -> Result<(), String> {
+        let mut timer = self.timer.write();
+
+        if timer.current_phase() != TimerPhase::NotRunning {
+            return Err("The timer is already running".to_owned());
+        }
+
+        let method = timer.current_timing_method();
+        let run = timer.run();
+
+        if index >= run.len() {
+            return Err(format!("There is no segment {}", index + 1));
+        }
+
+        let start_time = match index.checked_sub(1) {
+            Some(previous) => {
+                let segment = run.segment(previous);
+                segment.personal_best_split_time()[method]
+                    .ok_or_else(|| format!("{} has no personal best time", segment.name()))?
+            }
+            None => TimeSpan::zero(),
+        };
+
+        // the offset is where the timer starts, the real one is put back on reset
+        let mut run = run.clone();
+        let offset = run.offset();
+        run.set_offset(start_time);
+        timer
+            .replace_run(run, false)
+            .map_err(|_| "The splits have no segments".to_owned())?;
+        self.offset_to_restore = Some(offset);
+
+        timer.start();
+
+        for _ in 0..index {
+            timer.skip_split();
+        }
+
+        self.practice_mode_to_restore = Some(self.practice_mode);
+        self.practice_mode = true;
+
+        Ok(())
+    }
+}
+
+pub(crate) fn show_start_error(e: &str) {
+    MessageDialog::new()
+        .set_title("Failed to Start")
+        .set_level(MessageLevel::Error)
+        .set_description(e)
+        .show();
+}
+
+/// Appends the attempt that's about to be reset to the practice log next to the config. One line
+/// per attempt, tab separated: when it ended in seconds since the epoch, the game, the category,
+/// how many segments were done and the time it ended on.
+pub fn log_attempt(timer: &Timer) -> Result<()> {
+    let path = Configuration::get_path()
+        .context("There is no config directory")?
+        .with_file_name("practice_log.txt");
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+
+    let run = timer.run();
+    let method = timer.current_timing_method();
+    let ended = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+
+    writeln!(
+        file,
+        "{ended}\t{}\t{}\t{}/{}\t{}",
+        run.game_name(),
+        run.category_name(),
+        timer.current_split_index().unwrap_or_default(),
+        run.len(),
+        format_time(timer.snapshot().current_time()[method]),
+    )?;
+
+    Ok(())
+}
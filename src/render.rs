@@ -0,0 +1,195 @@
+use std::{
+    fs::File,
+    io::BufWriter,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use livesplit_core::{
+    layout::LayoutState, rendering::software::BorrowedRenderer, Layout, TimeSpan, Timer,
+    TimingMethod,
+};
+
+use crate::{
+    configuration::Configuration,
+    utils::{load_layout_from_file, load_splits_from_file},
+};
+
+const USAGE: &str = "usage: lsod-egui render [--splits <file.lss>] [--layout <file.lsl>] \
+                     [--size <width>x<height>] [--phase <not-running|running|paused|ended>] \
+                     [--split <index>] [--no-config] --output <file.png>";
+
+/// Renders a layout with the same software renderer the timer window uses, without needing a
+/// window. Returns the image as straight (not premultiplied) RGBA.
+pub fn render_layout(layout: &mut Layout, timer: &Timer, [width, height]: [u32; 2]) -> Vec<u8> {
+    let mut layout_state = LayoutState::default();
+    layout.update_state(&mut layout_state, &timer.snapshot());
+
+    let mut image = vec![0; width as usize * height as usize * 4];
+    BorrowedRenderer::new().render(&layout_state, &mut image, [width, height], width, true);
+
+    // the renderer gives us premultiplied alpha, png wants it straight
+    for pixel in image.chunks_exact_mut(4) {
+        let alpha = pixel[3] as u32;
+
+        if alpha != 0 && alpha != 255 {
+            for channel in &mut pixel[..3] {
+                *channel = (*channel as u32 * 255 / alpha).min(255) as u8;
+            }
+        }
+    }
+
+    image
+}
+
+pub fn save_png(path: &Path, image: &[u8], [width, height]: [u32; 2]) -> Result<()> {
+    let file = BufWriter::new(File::create(path)?);
+
+    let mut encoder = png::Encoder::new(file, width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    encoder.write_header()?.write_image_data(image)?;
+
+    Ok(())
+}
+
+fn parse_size(size: &str) -> Result<[u32; 2]> {
+    let (width, height) = size
+        .split_once('x')
+        .ok_or_else(|| anyhow!("size has to look like 320x240, got {size}"))?;
+
+    let size = [width.parse()?, height.parse()?];
+
+    if size.contains(&0) {
+        bail!("size can't be zero");
+    }
+
+    Ok(size)
+}
+
+#[derive(Clone, Copy)]
+enum Phase {
+    NotRunning,
+    Running,
+    Paused,
+    Ended,
+}
+
+fn parse_phase(phase: &str) -> Result<Phase> {
+    Ok(match phase {
+        "not-running" => Phase::NotRunning,
+        "running" => Phase::Running,
+        "paused" => Phase::Paused,
+        "ended" => Phase::Ended,
+        _ => bail!("phase has to be not-running, running, paused or ended, got {phase}"),
+    })
+}
+
+/// Puts the timer into the given phase with `splits` segments already done. Every segment takes
+/// exactly its best segment time in game time, so the image doesn't depend on when or how fast
+/// it was rendered. Finishing like that is always a new personal best.
+fn simulate(timer: &mut Timer, phase: Phase, splits: usize) {
+    if let Phase::NotRunning = phase {
+        return;
+    }
+
+    timer.set_current_timing_method(TimingMethod::GameTime);
+    timer.start();
+    timer.initialize_game_time();
+    timer.pause_game_time();
+
+    let splits = match phase {
+        Phase::Ended => timer.run().len(),
+        // the last split would end the run
+        _ => splits.min(timer.run().len() - 1),
+    };
+
+    let mut game_time = TimeSpan::zero();
+
+    for index in 0..splits {
+        let best_segment_time = timer.run().segment(index).best_segment_time();
+
+        game_time += best_segment_time[TimingMethod::GameTime]
+            .or(best_segment_time[TimingMethod::RealTime])
+            .unwrap_or_else(|| TimeSpan::from_seconds(60.));
+
+        timer.set_game_time(game_time);
+        timer.split();
+    }
+
+    if let Phase::Paused = phase {
+        timer.pause();
+    }
+}
+
+/// The `render` subcommand. Anything that isn't passed on the command line comes from the
+/// configuration, unless `--no-config` is given.
+pub fn render_command(args: &[String]) -> Result<()> {
+    let mut splits_path = None;
+    let mut layout_path = None;
+    let mut size = None;
+    let mut output = None;
+    let mut phase = Phase::NotRunning;
+    let mut splits = 0;
+    let mut use_config = true;
+
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .with_context(|| format!("{arg} needs a value\n{USAGE}"))
+        };
+
+        match arg.as_str() {
+            "--splits" => splits_path = Some(PathBuf::from(value()?)),
+            "--layout" => layout_path = Some(PathBuf::from(value()?)),
+            "--size" => size = Some(parse_size(value()?)?),
+            "--output" => output = Some(PathBuf::from(value()?)),
+            "--phase" => phase = parse_phase(value()?)?,
+            "--split" => splits = value()?.parse()?,
+            "--no-config" => use_config = false,
+            _ => bail!("unknown argument {arg}\n{USAGE}"),
+        }
+    }
+
+    let output = output.with_context(|| format!("no output file given\n{USAGE}"))?;
+
+    let config = if use_config {
+        // the warning dialog makes no sense here
+        Configuration::load()
+            .context("Failed to load config")?
+            .unwrap_or_default()
+    } else {
+        Configuration::default()
+    };
+
+    let run = match splits_path
+        .as_ref()
+        .or(config.profile().splits_path.as_ref())
+    {
+        Some(path) => load_splits_from_file(path).context("Failed to load splits")?,
+        None => config.run_or_default(),
+    };
+
+    let mut layout = match layout_path
+        .as_ref()
+        .or(config.profile().layout_path.as_ref())
+    {
+        Some(path) => load_layout_from_file(path).context("Failed to load layout")?,
+        None => Layout::default_layout(),
+    };
+
+    // there's no monitor to scale for, so a logical pixel is a pixel
+    let size = size.unwrap_or_else(|| {
+        let size = config.profile().window_size().to_physical(1.);
+        [size.width, size.height]
+    });
+
+    let mut timer = Timer::new(run).map_err(|_| anyhow!("The splits don't have any segments"))?;
+    simulate(&mut timer, phase, splits);
+
+    let image = render_layout(&mut layout, &timer, size);
+    save_png(&output, &image, size).context("Failed to save image")
+}
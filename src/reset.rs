@@ -0,0 +1,195 @@
+use livesplit_core::{Run, TimeSpan, Timer, TimerPhase, TimingMethod};
+use rfd::{MessageButtons, MessageDialog};
+use serde::{Deserialize, Serialize};
+
+use crate::{practice, utils::save_splits, SharedState};
+
+/// Whether resetting puts the attempt into the splits, i.e. the attempt history, golds and pb.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ResetPolicy {
+    // only asks if there's something to lose
+    #[default]
+    AskOnNewBests,
+    AlwaysUpdate,
+    NeverUpdate,
+}
+
+impl ResetPolicy {
+    pub fn description(self) -> &'static str {
+        match self {
+            ResetPolicy::AskOnNewBests => "Ask whether to update the splits after a gold or pb",
+            ResetPolicy::AlwaysUpdate => "Always update the splits",
+            ResetPolicy::NeverUpdate => "Never update the splits",
+        }
+    }
+}
+
+impl SharedState {
+    /// Resets the timer, updating the splits depending on the reset policy.
+    pub fn reset(&mut self) {
+        match self.ended_attempt.take() {
+            // decided and saved when the timer reached the end
+            Some(update_splits) => self.reset_with(update_splits, update_splits),
+            None => {
+                let update_splits = self.should_update_splits();
+                self.reset_with(update_splits, false);
+            }
+        }
+    }
+
+    /// Resets the timer without putting the attempt into the splits.
+    pub fn reset_and_discard(&mut self) {
+        self.ended_attempt = None;
+        self.reset_with(false, false);
+    }
+
+    /// Ends the attempt like a reset, after giving an attempt that reached the end its chance to
+    /// be saved. For everything that replaces the run, which would reset the timer anyway.
+    pub fn finish_attempt(&mut self) {
+        self.check_for_end();
+        self.reset();
+    }
+
+    /// Called every frame to notice the timer reaching the end, however it got there. Whether the
+    /// attempt goes into the splits is decided right away, and if it does they're saved.
+    pub fn check_for_end(&mut self) {
+        if self.timer.read().current_phase() != TimerPhase::Ended {
+            // e.g. the last split was undone
+            self.ended_attempt = None;
+            return;
+        }
+
+        if self.ended_attempt.is_some() {
+            return;
+        }
+
+        let update_splits = self.should_update_splits();
+        self.ended_attempt = Some(update_splits);
+
+        if update_splits {
+            // resetting would clear the final times from the layout, so a copy is reset instead.
+            // the edits queued during the attempt are made on reset, so they're saved now too
+            let run = self.with_queued_edits(self.timer.read().clone().into_run(true));
+            save_splits(&run).unwrap_or_else(|e| eprintln!("Failed to save splits, got error {e}"));
+        }
+    }
+
+    // asks if the reset policy says so
+    fn should_update_splits(&self) -> bool {
+        if self.practice_mode {
+            return false;
+        }
+
+        match self.config.reset_policy {
+            ResetPolicy::AskOnNewBests => {
+                // the dialog blocks, the timer shouldn't be locked while it's open
+                let has_new_bests = has_new_bests(&self.timer.read());
+
+                !has_new_bests
+                    || MessageDialog::new()
+                        .set_buttons(MessageButtons::YesNo)
+                        .set_title("Update Splits?")
+                        .set_description(
+                            "You got a gold or a new personal best, would you like to update your splits?",
+                        )
+                        .show()
+            }
+            ResetPolicy::AlwaysUpdate => true,
+            ResetPolicy::NeverUpdate => false,
+        }
+    }
+
+    // in practice mode the attempt is thrown away no matter what
+    fn reset_with(&mut self, update_splits: bool, already_saved: bool) {
+        let mut timer = self.timer.write();
+
+        if !self.practice_mode {
+            timer.reset(update_splits);
+        } else if timer.current_phase() != TimerPhase::NotRunning {
+            if self.config.practice_log {
+                practice::log_attempt(&timer)
+                    .unwrap_or_else(|e| eprintln!("Failed to write practice log, got error {e:#}"));
+            }
+
+            timer.reset(false);
+
+            // starting the attempt already counted it
+            let mut run = timer.run().clone();
+            run.set_attempt_count(run.attempt_count().saturating_sub(1));
+            if let Some(offset) = self.offset_to_restore.take() {
+                run.set_offset(offset);
+            }
+            timer.replace_run(run, false).ok();
+        }
+
+        drop(timer);
+
+        if let Some(practice_mode) = self.practice_mode_to_restore.take() {
+            self.practice_mode = practice_mode;
+        }
+
+        self.apply_queued_edits();
+
+        // the saved splits already have the attempt and the queued edits
+        if already_saved {
+            self.timer.write().mark_as_unmodified();
+        }
+    }
+}
+
+/// Whether the current attempt has a faster segment than the golds, or finished faster than the
+/// pb. Works like livesplit: golds count for both timing methods, the pb only for the current one.
+fn has_new_bests(timer: &Timer) -> bool {
+    if timer.current_phase() == TimerPhase::NotRunning {
+        return false;
+    }
+
+    let done = timer
+        .current_split_index()
+        .unwrap_or_default()
+        .min(timer.run().len());
+
+    let new_gold = [TimingMethod::RealTime, TimingMethod::GameTime]
+        .into_iter()
+        .any(|method| (0..done).any(|index| is_gold(timer.run(), index, method)));
+
+    is_new_pb(timer) || new_gold
+}
+
+/// Whether the attempt ended faster than the pb, on the current timing method.
+pub(crate) fn is_new_pb(timer: &Timer) -> bool {
+    if timer.current_phase() != TimerPhase::Ended {
+        return false;
+    }
+
+    let run = timer.run();
+    let last = &run.segments()[run.len() - 1];
+    let method = timer.current_timing_method();
+
+    match (
+        last.split_time()[method],
+        last.personal_best_split_time()[method],
+    ) {
+        (Some(time), Some(pb)) => time < pb,
+        (Some(_), None) => true,
+        (None, _) => false,
+    }
+}
+
+/// Whether the segment of the current attempt was faster than its gold.
+pub(crate) fn is_gold(run: &Run, index: usize, method: TimingMethod) -> bool {
+    // skipped splits don't make a segment time
+    let previous = match index.checked_sub(1) {
+        Some(previous) => run.segment(previous).split_time()[method],
+        None => Some(TimeSpan::zero()),
+    };
+
+    let segment = run.segment(index);
+
+    match (previous, segment.split_time()[method]) {
+        (Some(previous), Some(split_time)) => {
+            segment.best_segment_time()[method].is_none_or(|best| split_time - previous < best)
+        }
+        _ => false,
+    }
+}
@@ -0,0 +1,238 @@
+use std::mem::replace;
+
+use livesplit_core::{
+    component::splits,
+    layout::{ComponentState, LayoutState},
+    run::editor::Editor,
+    Run, TimerPhase, TimingMethod,
+};
+
+use crate::{utils::save_splits, SharedState};
+
+/// An edit to the splits that can be made after the fact. The timer's run can't change under a
+/// running attempt, so edits made during one are queued and made on the next reset, to the run
+/// with the attempt in it. Cosmetic edits are saved and shown on the timer right away.
+#[derive(Clone)]
+pub enum RunEdit {
+    GameName(String),
+    CategoryName(String),
+    SegmentName(usize, String),
+    // the image file's contents, None removes the icon
+    GameIcon(Option<Vec<u8>>),
+    SegmentIcon(usize, Option<Vec<u8>>),
+    Offset(String),
+    AttemptCount(String),
+    SplitTime(usize, TimingMethod, String),
+    SegmentTime(usize, TimingMethod, String),
+    BestSegmentTime(usize, TimingMethod, String),
+}
+
+impl RunEdit {
+    pub fn apply(&self, editor: &mut Editor) {
+        match self {
+            RunEdit::GameName(name) => editor.set_game_name(name),
+            RunEdit::CategoryName(name) => editor.set_category_name(name),
+            RunEdit::SegmentName(index, name) => {
+                editor.select_only(*index);
+                editor.active_segment().set_name(name);
+            }
+            RunEdit::GameIcon(Some(icon)) => editor.set_game_icon(icon.as_slice()),
+            RunEdit::GameIcon(None) => editor.remove_game_icon(),
+            RunEdit::SegmentIcon(index, icon) => {
+                editor.select_only(*index);
+
+                match icon {
+                    Some(icon) => editor.active_segment().set_icon(icon.as_slice()),
+                    None => editor.active_segment().remove_icon(),
+                }
+            }
+            RunEdit::Offset(text) => {
+                editor.parse_and_set_offset(text).ok();
+            }
+            RunEdit::AttemptCount(text) => {
+                editor.parse_and_set_attempt_count(text).ok();
+            }
+            RunEdit::SplitTime(index, method, text) => {
+                editor.select_timing_method(*method);
+                editor.select_only(*index);
+                editor.active_segment().parse_and_set_split_time(text).ok();
+            }
+            RunEdit::SegmentTime(index, method, text) => {
+                editor.select_timing_method(*method);
+                editor.select_only(*index);
+                editor
+                    .active_segment()
+                    .parse_and_set_segment_time(text)
+                    .ok();
+            }
+            RunEdit::BestSegmentTime(index, method, text) => {
+                editor.select_timing_method(*method);
+                editor.select_only(*index);
+                editor
+                    .active_segment()
+                    .parse_and_set_best_segment_time(text)
+                    .ok();
+            }
+        }
+    }
+
+    /// Whether the edit only changes how the splits look, not their times.
+    pub fn is_cosmetic(&self) -> bool {
+        matches!(
+            self,
+            RunEdit::GameName(_)
+                | RunEdit::CategoryName(_)
+                | RunEdit::SegmentName(..)
+                | RunEdit::GameIcon(_)
+                | RunEdit::SegmentIcon(..)
+        )
+    }
+}
+
+impl SharedState {
+    /// Queues edits made during an attempt, see `RunEdit`.
+    pub fn queue_edits(&mut self, edits: impl IntoIterator<Item = RunEdit>) {
+        let start = self.queued_edits.len();
+        self.queued_edits.extend(edits);
+
+        if self.queued_edits[start..]
+            .iter()
+            .any(|edit| matches!(edit, RunEdit::GameIcon(_) | RunEdit::SegmentIcon(..)))
+        {
+            self.queued_icons_shown = false;
+        }
+    }
+
+    /// Saves the splits with the cosmetic edits queued during the attempt, so they're kept even
+    /// if the attempt isn't. The times are only edited on reset.
+    pub fn save_cosmetic_edits(&self) -> anyhow::Result<()> {
+        let timer = self.timer.read();
+
+        let mut run = self.with_edits(timer.run().clone(), RunEdit::is_cosmetic);
+
+        // starting a practice attempt counted it, but it's not going to be recorded
+        if self.practice_mode && timer.current_phase() != TimerPhase::NotRunning {
+            run.set_attempt_count(run.attempt_count().saturating_sub(1));
+        }
+
+        save_splits(&run)
+    }
+
+    pub(crate) fn apply_queued_edits(&mut self) {
+        let mut timer = self.timer.write();
+
+        if self.queued_edits.is_empty() || timer.current_phase() != TimerPhase::NotRunning {
+            return;
+        }
+
+        let run = self.with_queued_edits(timer.run().clone());
+        self.queued_edits.clear();
+
+        timer.replace_run(run, false).ok();
+    }
+
+    /// The run with the queued edits made to it, e.g. for saving the splits before the reset
+    /// makes them to the timer's run.
+    pub(crate) fn with_queued_edits(&self, run: Run) -> Run {
+        self.with_edits(run, |_| true)
+    }
+
+    fn with_edits(&self, run: Run, filter: impl Fn(&RunEdit) -> bool) -> Run {
+        if !self.queued_edits.iter().any(&filter) {
+            return run;
+        }
+
+        let mut editor = match Editor::new(run.clone()) {
+            Ok(editor) => editor,
+            Err(e) => {
+                eprintln!("Failed to edit splits, got error {e}");
+                return run;
+            }
+        };
+
+        for edit in self.queued_edits.iter().filter(|edit| filter(edit)) {
+            edit.apply(&mut editor);
+        }
+
+        editor.close()
+    }
+
+    /// The cosmetic queued edits show on the timer right away, by changing the layout state.
+    /// Icons are only sent to the layout state once, like the layout does when the run's icons
+    /// change.
+    pub(crate) fn show_queued_edits(&mut self, layout_state: &mut LayoutState) {
+        if self.queued_edits.is_empty() {
+            return;
+        }
+
+        let show_icons = !replace(&mut self.queued_icons_shown, true);
+
+        let (old_game, old_category) = {
+            let timer = self.timer.read();
+            let run = timer.run();
+            (run.game_name().to_owned(), run.category_name().to_owned())
+        };
+        let (mut game, mut category) = (old_game.clone(), old_category.clone());
+
+        for edit in &self.queued_edits {
+            match edit {
+                RunEdit::GameName(name) => game = name.clone(),
+                RunEdit::CategoryName(name) => category = name.clone(),
+                _ => {}
+            }
+        }
+
+        for component in &mut layout_state.components {
+            match component {
+                ComponentState::Splits(state) => {
+                    for edit in &self.queued_edits {
+                        match edit {
+                            RunEdit::SegmentName(index, name) => {
+                                for split in &mut state.splits {
+                                    if split.index == *index {
+                                        split.name = name.as_str().into();
+                                    }
+                                }
+                            }
+                            RunEdit::SegmentIcon(index, icon) if show_icons => {
+                                state.icon_changes.push(splits::IconChange {
+                                    segment_index: *index,
+                                    // no data is no icon
+                                    icon: icon.clone().unwrap_or_default().into(),
+                                });
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+                ComponentState::Title(state) => {
+                    // game and category on one line, or each on their own
+                    if shows(&state.line1, &format!("{old_game} - {old_category}")) {
+                        state.line1 = vec![format!("{game} - {category}").into()];
+                    } else if shows(&state.line1, &old_game) {
+                        state.line1 = vec![game.as_str().into()];
+                    }
+
+                    if shows(&state.line2, &old_category) {
+                        state.line2 = vec![category.as_str().into()];
+                    }
+
+                    if show_icons {
+                        for edit in &self.queued_edits {
+                            if let RunEdit::GameIcon(icon) = edit {
+                                state.icon_change = Some(icon.clone().unwrap_or_default().into());
+                            }
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+// the first line of the title is the whole name, the others are abbreviations of it. names the
+// layout changed, e.g. with variables, aren't recognized and stay as they are
+fn shows(lines: &[impl AsRef<str>], name: &str) -> bool {
+    lines.first().is_some_and(|line| line.as_ref() == name)
+}
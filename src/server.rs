@@ -0,0 +1,341 @@
+use std::{
+    io::{self, BufRead, BufReader, ErrorKind, Write},
+    net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Sender},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+use livesplit_core::{analysis::state_helper, TimeSpan, Timer, TimerPhase, TimingMethod};
+use serde::{Deserialize, Serialize};
+
+use crate::{event_sink::EventSender, utils::format_time, UserEvent};
+
+// how often the server threads check whether they should shut down
+pub(crate) const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ServerConfig {
+    pub enabled: bool,
+    pub address: Ipv4Addr,
+    pub port: u16,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            address: Ipv4Addr::LOCALHOST,
+            // the port LiveSplit's server uses
+            port: 16834,
+        }
+    }
+}
+
+/// Where the event loop sends the response to a `Command`.
+pub type ResponseSender = Sender<Option<String>>;
+
+/// A command of LiveSplit's server protocol.
+pub enum Command {
+    StartTimer,
+    StartOrSplit,
+    Split,
+    Unsplit,
+    SkipSplit,
+    Pause,
+    Resume,
+    Reset,
+    InitGameTime,
+    SetGameTime(TimeSpan),
+    SetLoadingTimes(TimeSpan),
+    PauseGameTime,
+    UnpauseGameTime,
+    SetComparison(String),
+    SwitchTo(TimingMethod),
+    GetDelta(Option<String>),
+    GetLastSplitTime,
+    GetComparisonSplitTime,
+    GetCurrentTime,
+    GetFinalTime(Option<String>),
+    GetSplitIndex,
+    GetCurrentSplitName,
+    GetPreviousSplitName,
+    GetCurrentTimerPhase,
+    GetAttemptCount,
+    GetCompletedCount,
+    Ping,
+}
+
+impl Command {
+    pub fn parse(line: &str) -> Option<Command> {
+        let (command, argument) = match line.trim().split_once(' ') {
+            Some((command, argument)) => (command, Some(argument.trim())),
+            None => (line.trim(), None),
+        };
+
+        let time = || argument?.parse::<TimeSpan>().ok();
+        let comparison = || argument.map(str::to_owned);
+
+        Some(match command {
+            "starttimer" => Command::StartTimer,
+            "startorsplit" => Command::StartOrSplit,
+            "split" => Command::Split,
+            "unsplit" => Command::Unsplit,
+            "skipsplit" => Command::SkipSplit,
+            "pause" => Command::Pause,
+            "resume" => Command::Resume,
+            "reset" => Command::Reset,
+            "initgametime" => Command::InitGameTime,
+            "setgametime" => Command::SetGameTime(time()?),
+            "setloadingtimes" => Command::SetLoadingTimes(time()?),
+            "pausegametime" => Command::PauseGameTime,
+            "unpausegametime" => Command::UnpauseGameTime,
+            "setcomparison" => Command::SetComparison(comparison()?),
+            "switchto" => Command::SwitchTo(match argument? {
+                "realtime" => TimingMethod::RealTime,
+                "gametime" => TimingMethod::GameTime,
+                _ => return None,
+            }),
+            "getdelta" => Command::GetDelta(comparison()),
+            "getlastsplittime" => Command::GetLastSplitTime,
+            "getcomparisonsplittime" => Command::GetComparisonSplitTime,
+            "getcurrenttime" => Command::GetCurrentTime,
+            "getfinaltime" => Command::GetFinalTime(comparison()),
+            "getsplitindex" => Command::GetSplitIndex,
+            "getcurrentsplitname" => Command::GetCurrentSplitName,
+            "getprevioussplitname" => Command::GetPreviousSplitName,
+            "getcurrenttimerphase" => Command::GetCurrentTimerPhase,
+            "getattemptcount" => Command::GetAttemptCount,
+            "getcompletedcount" => Command::GetCompletedCount,
+            "ping" => Command::Ping,
+            _ => return None,
+        })
+    }
+
+    /// Runs the command, returning the response if the command has one. Resets need more than the
+    /// timer, outside the crate commands run through `SharedState::run_command`.
+    pub(crate) fn execute(self, timer: &mut Timer) -> Option<String> {
+        let method = timer.current_timing_method();
+
+        match self {
+            Command::StartTimer => timer.start(),
+            Command::StartOrSplit => timer.split_or_start(),
+            Command::Split => timer.split(),
+            Command::Unsplit => timer.undo_split(),
+            Command::SkipSplit => timer.skip_split(),
+            Command::Pause => timer.pause(),
+            Command::Resume => timer.resume(),
+            Command::Reset => unreachable!("resets go through the shared state"),
+            Command::InitGameTime => timer.initialize_game_time(),
+            Command::SetGameTime(time) => timer.set_game_time(time),
+            Command::SetLoadingTimes(time) => timer.set_loading_times(time),
+            Command::PauseGameTime => timer.pause_game_time(),
+            Command::UnpauseGameTime => timer.resume_game_time(),
+            Command::SetComparison(comparison) => {
+                timer.set_current_comparison(comparison).ok();
+            }
+            Command::SwitchTo(method) => timer.set_current_timing_method(method),
+            Command::GetDelta(comparison) => {
+                let comparison = comparison.as_deref().unwrap_or(timer.current_comparison());
+                let delta = timer.current_split_index().and_then(|index| {
+                    state_helper::last_delta(timer.run(), index, comparison, method)
+                });
+
+                return Some(format_time(delta));
+            }
+            Command::GetLastSplitTime => {
+                let time = previous_split_index(timer)
+                    .and_then(|index| timer.run().segment(index).split_time()[method]);
+
+                return Some(format_time(time));
+            }
+            Command::GetComparisonSplitTime => {
+                let time = timer
+                    .current_split()
+                    .and_then(|segment| segment.comparison(timer.current_comparison())[method]);
+
+                return Some(format_time(time));
+            }
+            Command::GetCurrentTime => {
+                return Some(format_time(timer.snapshot().current_time()[method]));
+            }
+            Command::GetFinalTime(comparison) => {
+                let last_segment = timer.run().segments().last()?;
+
+                let time = match (timer.current_phase(), comparison) {
+                    (TimerPhase::Ended, None) => last_segment.split_time()[method],
+                    (_, comparison) => {
+                        let comparison =
+                            comparison.as_deref().unwrap_or(timer.current_comparison());
+                        last_segment.comparison(comparison)[method]
+                    }
+                };
+
+                return Some(format_time(time));
+            }
+            Command::GetSplitIndex => {
+                let index = timer.current_split_index().map_or(-1, |index| index as i64);
+
+                return Some(index.to_string());
+            }
+            Command::GetCurrentSplitName => {
+                return Some(
+                    timer
+                        .current_split()
+                        .map_or("-", |segment| segment.name())
+                        .to_owned(),
+                );
+            }
+            Command::GetPreviousSplitName => {
+                let name = previous_split_index(timer)
+                    .map_or("-", |index| timer.run().segment(index).name());
+
+                return Some(name.to_owned());
+            }
+            Command::GetCurrentTimerPhase => {
+                let phase = match timer.current_phase() {
+                    TimerPhase::NotRunning => "NotRunning",
+                    TimerPhase::Running => "Running",
+                    TimerPhase::Ended => "Ended",
+                    TimerPhase::Paused => "Paused",
+                };
+
+                return Some(phase.to_owned());
+            }
+            Command::GetAttemptCount => return Some(timer.run().attempt_count().to_string()),
+            Command::GetCompletedCount => {
+                let completed = timer
+                    .run()
+                    .attempt_history()
+                    .iter()
+                    .filter(|attempt| attempt.time().real_time.is_some())
+                    .count();
+
+                return Some(completed.to_string());
+            }
+            Command::Ping => return Some("pong".to_owned()),
+        }
+
+        None
+    }
+}
+
+fn previous_split_index(timer: &Timer) -> Option<usize> {
+    match timer.current_phase() {
+        TimerPhase::NotRunning => None,
+        TimerPhase::Ended => timer.run().len().checked_sub(1),
+        TimerPhase::Running | TimerPhase::Paused => timer.current_split_index()?.checked_sub(1),
+    }
+}
+
+/// A server speaking LiveSplit's text based protocol. Commands are sent to the event loop, which
+/// runs them against the timer and sends the response back. The server shuts down when dropped.
+pub struct Server {
+    stop: Arc<AtomicBool>,
+    address: SocketAddr,
+}
+
+impl Server {
+    pub fn start(config: &ServerConfig, send_event: EventSender) -> io::Result<Self> {
+        let listener = TcpListener::bind(SocketAddr::from((config.address, config.port)))?;
+        // we can't interrupt accept, so we poll instead
+        listener.set_nonblocking(true)?;
+
+        // port 0 picks a free port, so this is where it really is
+        let address = listener.local_addr()?;
+
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let thread_stop = stop.clone();
+        thread::spawn(move || listen(listener, send_event, thread_stop));
+
+        Ok(Self { stop, address })
+    }
+
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// Starts the server if it's enabled, showing any errors on the console.
+    pub fn from_config(config: &ServerConfig, send_event: EventSender) -> Option<Self> {
+        if !config.enabled {
+            return None;
+        }
+
+        Server::start(config, send_event)
+            .map_err(|e| eprintln!("Failed to start server, got error {e}"))
+            .ok()
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+fn listen(listener: TcpListener, send_event: EventSender, stop: Arc<AtomicBool>) {
+    while !stop.load(Ordering::Relaxed) {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                let send_event = send_event.clone();
+                let stop = stop.clone();
+
+                thread::spawn(move || {
+                    handle_connection(stream, send_event, stop)
+                        .unwrap_or_else(|e| eprintln!("Server connection failed, got error {e}"))
+                });
+            }
+            Err(e) if e.kind() == ErrorKind::WouldBlock => thread::sleep(POLL_INTERVAL),
+            Err(e) => eprintln!("Server failed to accept connection, got error {e}"),
+        }
+    }
+}
+
+fn handle_connection(
+    stream: TcpStream,
+    send_event: EventSender,
+    stop: Arc<AtomicBool>,
+) -> io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(POLL_INTERVAL))?;
+
+    let mut writer = stream.try_clone()?;
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+
+    while !stop.load(Ordering::Relaxed) {
+        match reader.read_line(&mut line) {
+            // the client disconnected
+            Ok(0) => return Ok(()),
+            Ok(_) => {}
+            Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => continue,
+            Err(e) => return Err(e),
+        }
+
+        if let Some(command) = Command::parse(&line) {
+            let (sender, receiver) = mpsc::channel();
+
+            if send_event
+                .send_event(UserEvent::ServerCommand(command, sender))
+                .is_err()
+            {
+                // the event loop is gone, so we are shutting down
+                return Ok(());
+            }
+
+            if let Ok(Some(response)) = receiver.recv() {
+                writeln!(writer, "{response}")?;
+            }
+        }
+
+        line.clear();
+    }
+
+    Ok(())
+}
@@ -1,12 +1,13 @@
 use anyhow::Result;
 use livesplit_core::{
     layout::{self, LayoutSettings},
-    run::parser::composite,
-    Layout, Run,
+    run::{parser::composite, saver::livesplit::save_run},
+    timing::formatter::{Regular, TimeFormatter},
+    Layout, Run, TimeSpan, TimingMethod,
 };
 use std::{
     fs::File,
-    io::{BufReader, Seek, SeekFrom},
+    io::{BufReader, BufWriter, Seek, SeekFrom},
     path::PathBuf,
 };
 
@@ -20,8 +21,27 @@ pub fn load_layout_from_file(path: &PathBuf) -> Result<Layout> {
 }
 
 pub fn load_splits_from_file(path: &PathBuf) -> Result<Run> {
-    let file = BufReader::new(File::open(&path)?);
+    let file = BufReader::new(File::open(path)?);
     let mut run = composite::parse(file, Some(path.clone()), true)?.run;
     run.fix_splits();
     Ok(run)
 }
+
+/// Saves the splits where they were loaded from, if they were.
+pub fn save_splits(run: &Run) -> Result<()> {
+    if let Some(path) = run.path() {
+        save_run(run, BufWriter::new(File::create(path)?))?;
+    }
+    Ok(())
+}
+
+pub fn format_time(time: Option<TimeSpan>) -> String {
+    Regular::new().format(time).to_string()
+}
+
+pub fn timing_method_name(method: TimingMethod) -> &'static str {
+    match method {
+        TimingMethod::RealTime => "Real Time",
+        TimingMethod::GameTime => "Game Time",
+    }
+}
@@ -0,0 +1,232 @@
+use std::{
+    io::{self, ErrorKind},
+    net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+use livesplit_core::{layout::LayoutState, parking_lot::Mutex};
+use serde::{Deserialize, Serialize};
+use tungstenite::{protocol::WebSocketConfig as ProtocolConfig, Message, WebSocket};
+
+use crate::server::{Command, POLL_INTERVAL};
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WebSocketConfig {
+    pub enabled: bool,
+    pub address: Ipv4Addr,
+    pub port: u16,
+}
+
+impl Default for WebSocketConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            address: Ipv4Addr::LOCALHOST,
+            port: 16835,
+        }
+    }
+}
+
+// a client that doesn't finish its handshake in time is dropped, so it can't hold up the others
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+
+// how many messages can wait for a client that doesn't read them before it's dropped
+const MAX_SEND_QUEUE: usize = 16;
+
+struct Client {
+    socket: WebSocket<TcpStream>,
+    // whether it was sent the current layout state
+    up_to_date: bool,
+}
+
+type Clients = Arc<Mutex<Vec<Client>>>;
+
+/// Streams the layout state as JSON to every connected client, for browser source overlays.
+/// Clients can send the same commands as the LiveSplit server accepts. The server shuts down when
+/// dropped.
+pub struct WebSocketServer {
+    clients: Clients,
+    // the layout state as it was last sent
+    state: Mutex<String>,
+    stop: Arc<AtomicBool>,
+}
+
+impl WebSocketServer {
+    pub fn start(config: &WebSocketConfig) -> io::Result<Self> {
+        let listener = TcpListener::bind(SocketAddr::from((config.address, config.port)))?;
+        listener.set_nonblocking(true)?;
+
+        let clients = Clients::default();
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let thread_clients = clients.clone();
+        let thread_stop = stop.clone();
+        thread::spawn(move || listen(listener, thread_clients, thread_stop));
+
+        Ok(Self {
+            clients,
+            state: Mutex::default(),
+            stop,
+        })
+    }
+
+    /// Starts the server if it's enabled, showing any errors on the console.
+    pub fn from_config(config: &WebSocketConfig) -> Option<Self> {
+        if !config.enabled {
+            return None;
+        }
+
+        WebSocketServer::start(config)
+            .map_err(|e| eprintln!("Failed to start WebSocket server, got error {e}"))
+            .ok()
+    }
+
+    /// Runs the commands clients sent since the last update, then sends them the layout state if
+    /// it changed. Called on every redraw.
+    pub fn update(
+        &self,
+        layout_state: &LayoutState,
+        mut run_command: impl FnMut(Command) -> Option<String>,
+    ) {
+        let mut clients = self.clients.lock();
+
+        if clients.is_empty() {
+            return;
+        }
+
+        let mut json = Vec::new();
+        if let Err(e) = layout_state.write_json(&mut json) {
+            eprintln!("Failed to serialize layout state, got error {e}");
+            return;
+        }
+        let json = String::from_utf8(json).unwrap();
+
+        let mut state = self.state.lock();
+        if *state != json {
+            for client in clients.iter_mut() {
+                client.up_to_date = false;
+            }
+            *state = json;
+        }
+
+        clients.retain_mut(
+            |client| match update_client(client, &state, &mut run_command) {
+                Ok(()) => true,
+                Err(tungstenite::Error::ConnectionClosed | tungstenite::Error::AlreadyClosed) => {
+                    false
+                }
+                Err(tungstenite::Error::SendQueueFull(_)) => {
+                    eprintln!(
+                        "WebSocket client doesn't keep up with the layout state, disconnecting"
+                    );
+                    false
+                }
+                Err(e) => {
+                    eprintln!("WebSocket connection failed, got error {e}");
+                    false
+                }
+            },
+        );
+    }
+}
+
+impl Drop for WebSocketServer {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+fn is_would_block(e: &tungstenite::Error) -> bool {
+    matches!(e, tungstenite::Error::Io(e) if e.kind() == ErrorKind::WouldBlock)
+}
+
+// tungstenite's own error, it's matched on right away
+#[allow(clippy::result_large_err)]
+fn update_client(
+    client: &mut Client,
+    json: &str,
+    run_command: &mut impl FnMut(Command) -> Option<String>,
+) -> tungstenite::Result<()> {
+    loop {
+        let message = match client.socket.read_message() {
+            Ok(message) => message,
+            Err(e) if is_would_block(&e) => break,
+            Err(e) => return Err(e),
+        };
+
+        if let Message::Text(text) = message {
+            if let Some(response) = Command::parse(&text).and_then(&mut *run_command) {
+                queue(&mut client.socket, Message::Text(response))?;
+            }
+        }
+    }
+
+    if !client.up_to_date {
+        queue(&mut client.socket, Message::Text(json.to_owned()))?;
+        client.up_to_date = true;
+    }
+
+    Ok(())
+}
+
+// the socket doesn't block, so messages that couldn't be sent yet stay queued in the WebSocket,
+// up to `MAX_SEND_QUEUE`
+#[allow(clippy::result_large_err)]
+fn queue(client: &mut WebSocket<TcpStream>, message: Message) -> tungstenite::Result<()> {
+    match client.write_message(message) {
+        Err(e) if is_would_block(&e) => Ok(()),
+        result => result,
+    }
+}
+
+fn listen(listener: TcpListener, clients: Clients, stop: Arc<AtomicBool>) {
+    while !stop.load(Ordering::Relaxed) {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                let clients = clients.clone();
+
+                // a client that's slow to shake hands only holds up its own thread
+                thread::spawn(move || match handshake(stream) {
+                    Ok(socket) => clients.lock().push(Client {
+                        socket,
+                        up_to_date: false,
+                    }),
+                    Err(e) => eprintln!("WebSocket handshake failed, got error {e}"),
+                });
+            }
+            Err(e) if e.kind() == ErrorKind::WouldBlock => thread::sleep(POLL_INTERVAL),
+            Err(e) => eprintln!("WebSocket server failed to accept connection, got error {e}"),
+        }
+    }
+}
+
+fn handshake(stream: TcpStream) -> Result<WebSocket<TcpStream>, String> {
+    stream.set_nonblocking(false).map_err(|e| e.to_string())?;
+    stream
+        .set_read_timeout(Some(HANDSHAKE_TIMEOUT))
+        .map_err(|e| e.to_string())?;
+    stream
+        .set_write_timeout(Some(HANDSHAKE_TIMEOUT))
+        .map_err(|e| e.to_string())?;
+
+    let config = ProtocolConfig {
+        max_send_queue: Some(MAX_SEND_QUEUE),
+        ..ProtocolConfig::default()
+    };
+    let socket =
+        tungstenite::accept_with_config(stream, Some(config)).map_err(|e| e.to_string())?;
+
+    // from now on the client is only polled from the event loop
+    let stream = socket.get_ref();
+    stream.set_read_timeout(None).map_err(|e| e.to_string())?;
+    stream.set_write_timeout(None).map_err(|e| e.to_string())?;
+    stream.set_nonblocking(true).map_err(|e| e.to_string())?;
+
+    Ok(socket)
+}
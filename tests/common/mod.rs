@@ -0,0 +1,36 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use lsod_egui::{configuration::Configuration, EventQueue, MemoryHotkeys, SharedState};
+
+/// Copies the test splits somewhere the test can save over them.
+pub fn splits_file(name: &str) -> PathBuf {
+    fixture_file("golden.lss", name)
+}
+
+/// Copies splits from the fixtures somewhere the test can save over them.
+pub fn fixture_file(fixture: &str, name: &str) -> PathBuf {
+    let fixture = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(fixture);
+    let path = Path::new(env!("CARGO_TARGET_TMPDIR")).join(format!("{name}.lss"));
+
+    fs::copy(fixture, &path).unwrap();
+
+    path
+}
+
+/// The default configuration with the given splits.
+pub fn config(splits_path: &Path) -> Configuration {
+    let mut config = Configuration::default();
+    config.profile_mut().splits_path = Some(splits_path.to_owned());
+    config
+}
+
+/// State that sends its events to `events` and doesn't listen to the keyboard, so it works
+/// without a display.
+pub fn shared_state(config: Configuration, events: &EventQueue) -> SharedState {
+    SharedState::with_backends::<MemoryHotkeys>(config, Box::new(events.clone()))
+}
@@ -0,0 +1,278 @@
+//! Where the configuration is, and how configurations from other versions of the timer are read.
+//! The location comes from environment variables, so these tests take turns.
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    sync::{Mutex, MutexGuard, PoisonError},
+};
+
+use lsod_egui::configuration::Configuration;
+
+static ENV: Mutex<()> = Mutex::new(());
+
+fn lock_env() -> MutexGuard<'static, ()> {
+    ENV.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Writes a configuration into a directory of its own and makes it the one that's used.
+fn config_file(name: &str, contents: &str) -> PathBuf {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR"))
+        .join("configuration")
+        .join(name);
+    fs::remove_dir_all(&dir).ok();
+    fs::create_dir_all(&dir).unwrap();
+
+    let path = dir.join("config.json");
+    fs::write(&path, contents).unwrap();
+    env::set_var("LSOD_CONFIG", &path);
+
+    path
+}
+
+fn backups(path: &Path) -> Vec<PathBuf> {
+    fs::read_dir(path.parent().unwrap())
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|backup| {
+            let name = backup.file_name().unwrap().to_str().unwrap();
+            name.starts_with("config.json.") && name.ends_with(".bak")
+        })
+        .collect()
+}
+
+fn portable_path() -> PathBuf {
+    env::current_exe()
+        .unwrap()
+        .with_file_name("lsod_config.json")
+}
+
+// removes the portable configuration even if the test fails, the other tests don't expect one
+struct PortableConfig;
+
+impl PortableConfig {
+    fn create() -> Self {
+        fs::write(portable_path(), "{}").unwrap();
+        PortableConfig
+    }
+}
+
+impl Drop for PortableConfig {
+    fn drop(&mut self) {
+        fs::remove_file(portable_path()).ok();
+    }
+}
+
+const UNVERSIONED: &str = r#"{
+    "splits_path": "/splits/any%.lss",
+    "size": [300, 500],
+    "practice_log": true
+}"#;
+
+#[test]
+fn the_environment_variable_decides_where_the_config_is() {
+    let _env = lock_env();
+    let path = config_file("the_environment_variable_decides_where_the_config_is", "{}");
+
+    assert_eq!(Configuration::get_path(), Some(path.clone()));
+
+    // even over a portable configuration
+    let _portable = PortableConfig::create();
+    assert_eq!(Configuration::get_path(), Some(path));
+}
+
+#[test]
+fn a_config_next_to_the_executable_makes_the_timer_portable() {
+    let _env = lock_env();
+    env::remove_var("LSOD_CONFIG");
+
+    let _portable = PortableConfig::create();
+    assert_eq!(Configuration::get_path(), Some(portable_path()));
+}
+
+#[test]
+fn otherwise_the_config_is_in_the_config_directory() {
+    let _env = lock_env();
+    env::remove_var("LSOD_CONFIG");
+
+    let path = Configuration::get_path().unwrap();
+    assert!(
+        path.ends_with("lsod-egui/config.json"),
+        "{}",
+        path.display()
+    );
+}
+
+// the config directory can only be moved with an environment variable on linux
+#[cfg(target_os = "linux")]
+#[test]
+fn the_config_is_moved_from_the_old_location() {
+    let _env = lock_env();
+    env::remove_var("LSOD_CONFIG");
+
+    let config_dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("config_home");
+    fs::remove_dir_all(&config_dir).ok();
+    fs::create_dir_all(&config_dir).unwrap();
+    env::set_var("XDG_CONFIG_HOME", &config_dir);
+
+    let old_path = config_dir.join("lsod_config.json");
+    fs::write(&old_path, UNVERSIONED).unwrap();
+
+    let config = Configuration::load();
+    env::remove_var("XDG_CONFIG_HOME");
+
+    assert!(config.unwrap().unwrap().practice_log);
+    assert!(!old_path.exists());
+    assert!(config_dir.join("lsod-egui/config.json").exists());
+}
+
+#[test]
+fn old_configs_are_brought_up_to_the_current_version() {
+    let _env = lock_env();
+    config_file(
+        "old_configs_are_brought_up_to_the_current_version",
+        UNVERSIONED,
+    );
+
+    let config = Configuration::load().unwrap().unwrap();
+
+    assert_eq!(config.version, Configuration::default().version);
+    assert!(config.practice_log);
+
+    // version 1 had no profiles
+    assert_eq!(config.profiles.len(), 1);
+    assert_eq!(config.current_profile, 0);
+    assert_eq!(config.profile().name, "Default");
+    assert_eq!(
+        config.profile().splits_path.as_deref(),
+        Some(Path::new("/splits/any%.lss"))
+    );
+    assert_eq!(config.profile().size, (300, 500));
+}
+
+#[test]
+fn old_sizes_are_converted_from_physical_pixels() {
+    let _env = lock_env();
+    config_file("old_sizes_are_converted_from_physical_pixels", UNVERSIONED);
+
+    let mut config = Configuration::load().unwrap().unwrap();
+    assert!(config.profile().size_is_physical);
+
+    config.convert_physical_sizes(2.);
+    assert_eq!(config.profile().size, (150, 250));
+    assert!(!config.profile().size_is_physical);
+
+    // only once, the size is logical now
+    config.convert_physical_sizes(2.);
+    assert_eq!(config.profile().size, (150, 250));
+}
+
+#[test]
+fn current_sizes_are_already_logical() {
+    let _env = lock_env();
+    let path = config_file("current_sizes_are_already_logical", "{}");
+    fs::remove_file(&path).unwrap();
+
+    Configuration::default().save().unwrap();
+    let mut config = Configuration::load().unwrap().unwrap();
+    config.convert_physical_sizes(2.);
+
+    assert_eq!(
+        config.profile().size,
+        Configuration::default().profile().size
+    );
+}
+
+#[test]
+fn zooms_out_of_range_are_reset() {
+    let _env = lock_env();
+    let contents = format!(
+        r#"{{"version": {}, "profiles": [{{"zoom": 10}}, {{"zoom": 0}}, {{"zoom": 2}}]}}"#,
+        Configuration::default().version
+    );
+    config_file("zooms_out_of_range_are_reset", &contents);
+
+    let config = Configuration::load().unwrap().unwrap();
+
+    let zooms: Vec<f64> = config.profiles.iter().map(|profile| profile.zoom).collect();
+    assert_eq!(zooms, [1., 1., 2.]);
+}
+
+#[test]
+fn scales_the_settings_dont_offer_are_reset() {
+    let _env = lock_env();
+    let contents = format!(
+        r#"{{"version": {}, "appearance": {{"scale": 4}}}}"#,
+        Configuration::default().version
+    );
+    config_file("scales_the_settings_dont_offer_are_reset", &contents);
+
+    let config = Configuration::load().unwrap().unwrap();
+    assert_eq!(config.appearance.scale, 1.);
+}
+
+#[test]
+fn every_migration_keeps_a_backup() {
+    let _env = lock_env();
+    let path = config_file("every_migration_keeps_a_backup", UNVERSIONED);
+
+    Configuration::load().unwrap().unwrap().save().unwrap();
+    assert_eq!(backups(&path).len(), 1);
+
+    // the backup of the first migration isn't overwritten by the second one
+    fs::write(&path, UNVERSIONED).unwrap();
+    Configuration::load().unwrap().unwrap();
+
+    let backups = backups(&path);
+    assert_eq!(backups.len(), 2);
+    for backup in backups {
+        assert_eq!(fs::read_to_string(backup).unwrap(), UNVERSIONED);
+    }
+}
+
+#[test]
+fn an_old_config_is_only_backed_up_once() {
+    let _env = lock_env();
+    let path = config_file("an_old_config_is_only_backed_up_once", UNVERSIONED);
+
+    Configuration::load().unwrap().unwrap();
+    Configuration::load().unwrap().unwrap();
+
+    assert_eq!(backups(&path).len(), 1);
+
+    // the sizes are still converted after the migrated config was saved
+    assert!(
+        Configuration::load()
+            .unwrap()
+            .unwrap()
+            .profile()
+            .size_is_physical
+    );
+}
+
+#[test]
+fn current_configs_are_not_backed_up() {
+    let _env = lock_env();
+    let path = config_file("current_configs_are_not_backed_up", "{}");
+    fs::remove_file(&path).unwrap();
+
+    Configuration::default().save().unwrap();
+    Configuration::load().unwrap().unwrap();
+
+    assert!(backups(&path).is_empty());
+}
+
+#[test]
+fn configs_from_newer_versions_are_not_saved_over() {
+    let _env = lock_env();
+    let contents = r#"{"version": 1000, "from_the_future": true}"#;
+    let path = config_file("configs_from_newer_versions_are_not_saved_over", contents);
+
+    assert!(Configuration::load().is_err());
+
+    let (config, error) = Configuration::load_or_default();
+    assert!(error.is_some());
+    assert!(config.save().is_err());
+
+    assert_eq!(fs::read_to_string(&path).unwrap(), contents);
+}
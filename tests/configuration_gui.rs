@@ -0,0 +1,360 @@
+//! Drives the configuration window's UI through a bare egui context, the same way a user would:
+//! moving the focus with tab, typing and pressing enter.
+
+use std::{fs, mem::take, path::Path};
+
+use egui_glow::egui_winit::egui::{
+    self, output::OutputEvent, Event, Key, Modifiers, Pos2, RawInput, Rect, Vec2, WidgetInfo,
+    WidgetType,
+};
+use livesplit_core::{run::editor::Editor, Run};
+use lsod_egui::{
+    gui::{
+        appearance::{load_font, Theme},
+        ConfigurationUi,
+    },
+    EventQueue, SharedState, UserEvent,
+};
+
+mod common;
+
+// a frame per tab press, so this is how many widgets we look through before giving up
+const MAX_TABS: usize = 500;
+
+struct Harness {
+    ctx: egui::Context,
+    ui: ConfigurationUi,
+    shared_state: SharedState,
+    // what the ui sent to the event loop
+    events: EventQueue,
+    input: Vec<Event>,
+}
+
+impl Harness {
+    fn new(splits_path: &Path) -> Self {
+        let events = EventQueue::default();
+
+        let mut harness = Self {
+            ctx: egui::Context::default(),
+            ui: ConfigurationUi::new(),
+            shared_state: common::shared_state(common::config(splits_path), &events),
+            events,
+            input: Vec::new(),
+        };
+
+        harness.frame();
+        harness
+    }
+
+    fn frame(&mut self) -> Vec<OutputEvent> {
+        let input = RawInput {
+            screen_rect: Some(Rect::from_min_size(Pos2::ZERO, Vec2::new(1280., 800.))),
+            events: take(&mut self.input),
+            ..Default::default()
+        };
+
+        let ui = &mut self.ui;
+        let shared_state = &mut self.shared_state;
+
+        self.ctx
+            .run(input, |ctx| ui.show(ctx, shared_state))
+            .platform_output
+            .events
+    }
+
+    fn press(&mut self, key: Key) {
+        self.press_with(Modifiers::NONE, key);
+    }
+
+    fn press_with(&mut self, modifiers: Modifiers, key: Key) {
+        self.input.push(Event::Key {
+            key,
+            pressed: true,
+            modifiers,
+        });
+        self.frame();
+    }
+
+    fn type_text(&mut self, text: &str) {
+        self.input.push(Event::Text(text.to_owned()));
+        self.frame();
+    }
+
+    /// Tabs through the widgets until one that matches gets the focus.
+    fn focus(&mut self, what: &str, matches: impl Fn(&WidgetInfo) -> bool) {
+        for _ in 0..MAX_TABS {
+            self.input.push(Event::Key {
+                key: Key::Tab,
+                pressed: true,
+                modifiers: Modifiers::NONE,
+            });
+
+            let focused = self.frame().into_iter().any(|event| match event {
+                OutputEvent::FocusGained(info) => matches(&info),
+                _ => false,
+            });
+
+            if focused {
+                return;
+            }
+        }
+
+        panic!("couldn't find {what}");
+    }
+
+    fn click(&mut self, label: &str) {
+        self.focus(label, |info| {
+            info.typ == WidgetType::Button && info.label.as_deref() == Some(label)
+        });
+        self.press(Key::Enter);
+    }
+
+    fn rename_segment(&mut self, name: &str, suffix: &str) {
+        self.focus(name, |info| {
+            info.typ == WidgetType::TextEdit && info.current_text_value.as_deref() == Some(name)
+        });
+        // the cursor starts at the end of the text
+        self.type_text(suffix);
+    }
+
+    fn timer_segment_name(&self, index: usize) -> String {
+        let timer = self.shared_state.timer().read();
+        timer.run().segment(index).name().to_owned()
+    }
+
+    fn edited_segment_name(&self, index: usize) -> String {
+        let run = self.ui.edited_run().expect("the splits editor isn't open");
+        run.segment(index).name().to_owned()
+    }
+}
+
+#[test]
+fn renaming_a_segment_marks_the_splits_dirty() {
+    let path = common::splits_file("renaming_a_segment_marks_the_splits_dirty");
+    let mut harness = Harness::new(&path);
+
+    harness.click("Edit Splits");
+    assert!(!harness.ui.has_unsaved_changes());
+
+    harness.rename_segment("First", " Segment");
+
+    assert!(harness.ui.has_unsaved_changes());
+    assert_eq!(harness.edited_segment_name(0), "First Segment");
+    // nothing happens to the timer until the splits are saved
+    assert_eq!(harness.timer_segment_name(0), "First");
+}
+
+#[test]
+fn save_writes_the_splits_file() {
+    let path = common::splits_file("save_writes_the_splits_file");
+    let mut harness = Harness::new(&path);
+
+    harness.click("Edit Splits");
+    harness.rename_segment("Second", " Segment");
+    harness.click("Save");
+
+    assert!(!harness.ui.has_unsaved_changes());
+    assert_eq!(harness.timer_segment_name(1), "Second Segment");
+
+    let saved = fs::read_to_string(&path).unwrap();
+    assert!(saved.contains("<Name>Second Segment</Name>"));
+}
+
+#[test]
+fn discard_restores_the_run() {
+    let path = common::splits_file("discard_restores_the_run");
+    let original = fs::read_to_string(&path).unwrap();
+    let mut harness = Harness::new(&path);
+
+    harness.click("Edit Splits");
+    harness.rename_segment("Third", " Segment");
+    harness.click("Discard Changes");
+
+    assert!(!harness.ui.has_unsaved_changes());
+    assert_eq!(harness.edited_segment_name(2), "Third");
+    assert_eq!(harness.timer_segment_name(2), "Third");
+    assert_eq!(fs::read_to_string(&path).unwrap(), original);
+}
+
+#[test]
+fn changing_the_width_resizes_the_timer() {
+    let path = common::splits_file("changing_the_width_resizes_the_timer");
+    let mut harness = Harness::new(&path);
+
+    harness.click("Settings");
+
+    // the width is the field showing the default width, the server port is just before it
+    harness.focus("the width", |info| {
+        info.typ == WidgetType::TextEdit && info.current_text_value.as_deref() == Some("320")
+    });
+
+    for _ in 0.."320".len() {
+        harness.press(Key::Backspace);
+    }
+    harness.type_text("400");
+    harness.press(Key::Enter);
+
+    assert_eq!(harness.shared_state.config().profile().size, (400, 240));
+    assert!(harness
+        .events
+        .take()
+        .iter()
+        .any(|event| matches!(event, UserEvent::Resize)));
+}
+
+#[test]
+fn ctrl_s_saves_the_splits() {
+    let path = common::splits_file("ctrl_s_saves_the_splits");
+    let mut harness = Harness::new(&path);
+
+    harness.click("Edit Splits");
+    harness.rename_segment("First", " Segment");
+    harness.press_with(Modifiers::COMMAND, Key::S);
+
+    assert!(!harness.ui.has_unsaved_changes());
+    assert!(fs::read_to_string(&path)
+        .unwrap()
+        .contains("<Name>First Segment</Name>"));
+}
+
+#[test]
+fn arrow_keys_move_between_rows() {
+    let path = common::splits_file("arrow_keys_move_between_rows");
+    let mut harness = Harness::new(&path);
+
+    harness.click("Edit Splits");
+    harness.focus("First", |info| {
+        info.typ == WidgetType::TextEdit && info.current_text_value.as_deref() == Some("First")
+    });
+    harness.press(Key::ArrowDown);
+    harness.type_text(" Segment");
+
+    assert_eq!(harness.edited_segment_name(0), "First");
+    assert_eq!(harness.edited_segment_name(1), "Second Segment");
+}
+
+#[test]
+fn delete_removes_the_selected_segment() {
+    let path = common::splits_file("delete_removes_the_selected_segment");
+    let mut harness = Harness::new(&path);
+
+    harness.click("Edit Splits");
+    // the button still has the focus, and the keys would go to it
+    harness.press(Key::Escape);
+    harness.press(Key::Delete);
+
+    assert!(harness.ui.has_unsaved_changes());
+    let run = harness.ui.edited_run().unwrap();
+    assert_eq!(run.len(), 2);
+    assert_eq!(run.segment(0).name(), "Second");
+}
+
+#[test]
+fn the_theme_is_saved_in_the_configuration() {
+    let path = common::splits_file("the_theme_is_saved_in_the_configuration");
+    let mut harness = Harness::new(&path);
+
+    harness.click("Settings");
+    harness.focus("the light theme", |info| {
+        info.typ == WidgetType::RadioButton && info.label.as_deref() == Some("Light")
+    });
+    harness.press(Key::Enter);
+
+    assert!(harness.shared_state.config().appearance.theme == Theme::Light);
+}
+
+#[test]
+fn profiles_keep_their_name_unless_the_new_one_is_free() {
+    let path = common::splits_file("profiles_keep_their_name_unless_the_new_one_is_free");
+    let mut harness = Harness::new(&path);
+
+    harness.click("Profiles");
+    harness.focus("the new profile's name", |info| {
+        info.typ == WidgetType::TextEdit && info.current_text_value.as_deref() == Some("")
+    });
+    harness.type_text("Practice");
+    harness.click("New Profile");
+
+    let names = |harness: &Harness| -> Vec<String> {
+        let profiles = &harness.shared_state.config().profiles;
+        profiles
+            .iter()
+            .map(|profile| profile.name.clone())
+            .collect()
+    };
+    assert_eq!(names(&harness), ["Default", "Practice"]);
+
+    // the name of another profile
+    harness.focus("the new profile", |info| {
+        info.typ == WidgetType::TextEdit && info.current_text_value.as_deref() == Some("Practice")
+    });
+    // the cursor is wherever it was the last time
+    harness.press(Key::End);
+    for _ in 0.."Practice".len() {
+        harness.press(Key::Backspace);
+    }
+    harness.type_text("Default");
+    harness.press(Key::Enter);
+    assert_eq!(names(&harness), ["Default", "Practice"]);
+
+    // no name at all
+    harness.focus("the new profile", |info| {
+        info.typ == WidgetType::TextEdit && info.current_text_value.as_deref() == Some("Practice")
+    });
+    harness.press(Key::End);
+    for _ in 0.."Practice".len() {
+        harness.press(Key::Backspace);
+    }
+    harness.press(Key::Enter);
+    assert_eq!(names(&harness), ["Default", "Practice"]);
+
+    harness.focus("the new profile", |info| {
+        info.typ == WidgetType::TextEdit && info.current_text_value.as_deref() == Some("Practice")
+    });
+    harness.press(Key::End);
+    harness.type_text(" Runs");
+    harness.press(Key::Enter);
+    assert_eq!(names(&harness), ["Default", "Practice Runs"]);
+}
+
+#[test]
+fn fonts_egui_cannot_parse_are_rejected() {
+    let arial = Path::new(env!("CARGO_MANIFEST_DIR")).join("src/arial.ttf");
+    assert!(load_font(&arial).is_ok());
+
+    let broken = Path::new(env!("CARGO_TARGET_TMPDIR")).join("broken_font.ttf");
+    fs::write(&broken, b"not a font").unwrap();
+    assert!(load_font(&broken).is_err());
+}
+
+// the descriptions of everything the sum of best cleaner would suggest
+fn clean_ups(run: &Run) -> Vec<String> {
+    let mut editor = Editor::new(run.clone()).unwrap();
+    let mut cleaner = editor.clean_sum_of_best();
+    let mut clean_ups = Vec::new();
+
+    while let Some(clean_up) = cleaner.next_potential_clean_up() {
+        clean_ups.push(clean_up.to_string());
+    }
+
+    clean_ups
+}
+
+#[test]
+fn kept_clean_ups_stay_when_a_later_one_is_deleted() {
+    let path = common::fixture_file("cleaning.lss", "kept_clean_ups_stay");
+    let mut harness = Harness::new(&path);
+
+    let before = clean_ups(harness.shared_state.timer().read().run());
+    assert_eq!(before.len(), 2);
+
+    harness.click("Edit Splits");
+    harness.click("Clean Sum of Best");
+    harness.click("Keep");
+    harness.click("Delete");
+    // there's nothing left, so the wizard can only be closed
+    harness.click("Close");
+
+    assert!(harness.ui.has_unsaved_changes());
+    assert_eq!(clean_ups(harness.ui.edited_run().unwrap()), before[..1]);
+}
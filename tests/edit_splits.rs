@@ -0,0 +1,68 @@
+//! What the splits editor does to a run, without the editor's UI.
+
+use std::{fs::File, io::BufReader, path::Path};
+
+use livesplit_core::{run::parser::composite, Run, TimeSpan, TimingMethod};
+use lsod_egui::gui::remove_attempt;
+
+// two attempts, the second one has the gold of the first segment
+fn history_run() -> Run {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/history.lss");
+    composite::parse(BufReader::new(File::open(path).unwrap()), None, true)
+        .unwrap()
+        .run
+}
+
+fn best_segment_time(run: &Run, index: usize) -> Option<TimeSpan> {
+    run.segment(index).best_segment_time()[TimingMethod::RealTime]
+}
+
+#[test]
+fn removing_the_attempt_with_a_gold_recalculates_it() {
+    let mut run = history_run();
+    assert_eq!(best_segment_time(&run, 0), Some(TimeSpan::from_seconds(8.)));
+
+    remove_attempt(&mut run, 2);
+
+    assert_eq!(
+        best_segment_time(&run, 0),
+        Some(TimeSpan::from_seconds(10.))
+    );
+    assert!(run.has_been_modified());
+}
+
+#[test]
+fn removing_an_attempt_removes_its_segment_times() {
+    let mut run = history_run();
+
+    remove_attempt(&mut run, 2);
+
+    let attempts: Vec<_> = run
+        .attempt_history()
+        .iter()
+        .map(|attempt| attempt.index())
+        .collect();
+    assert_eq!(attempts, [1]);
+
+    for segment in run.segments() {
+        assert!(segment.segment_history().get(2).is_none());
+        assert!(segment.segment_history().get(1).is_some());
+    }
+}
+
+#[test]
+fn removing_an_attempt_only_recalculates_its_golds() {
+    let mut run = history_run();
+
+    remove_attempt(&mut run, 1);
+
+    assert_eq!(best_segment_time(&run, 0), Some(TimeSpan::from_seconds(8.)));
+    assert_eq!(
+        best_segment_time(&run, 1),
+        Some(TimeSpan::from_seconds(12.))
+    );
+    assert_eq!(
+        best_segment_time(&run, 2),
+        Some(TimeSpan::from_seconds(10.))
+    );
+}
@@ -0,0 +1,144 @@
+//! Renders the default layout in different timer phases through the `render` subcommand and
+//! compares the result against the reference images in `tests/golden`.
+//!
+//! A missing reference image fails the test. Set `LSOD_BLESS=1` to record the reference images
+//! from the current output, both for new tests and after an intended change to the rendering.
+
+use std::{
+    env,
+    fs::{self, File},
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+// how far a channel may be off before the pixel counts as different
+const CHANNEL_TOLERANCE: u8 = 8;
+// fraction of pixels that may be different, font rasterization isn't bit exact everywhere
+const MAX_DIFFERENT_PIXELS: f64 = 0.002;
+
+struct Image {
+    width: u32,
+    height: u32,
+    data: Vec<u8>,
+}
+
+fn load(path: &Path) -> Image {
+    let decoder = png::Decoder::new(File::open(path).unwrap());
+    let mut reader = decoder.read_info().unwrap();
+
+    let mut data = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut data).unwrap();
+    data.truncate(info.buffer_size());
+
+    assert_eq!(
+        info.color_type,
+        png::ColorType::Rgba,
+        "{} isn't RGBA",
+        path.display()
+    );
+
+    Image {
+        width: info.width,
+        height: info.height,
+        data,
+    }
+}
+
+fn render(name: &str, size: &str, args: &[&str]) -> PathBuf {
+    let fixture = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/golden.lss");
+    let output = Path::new(env!("CARGO_TARGET_TMPDIR")).join(format!("{name}.png"));
+
+    let status = Command::new(env!("CARGO_BIN_EXE_lsod-egui"))
+        .arg("render")
+        .arg("--no-config")
+        .arg("--splits")
+        .arg(&fixture)
+        .args(["--size", size])
+        .args(args)
+        .arg("--output")
+        .arg(&output)
+        .status()
+        .unwrap();
+
+    assert!(status.success(), "rendering {name} failed");
+
+    output
+}
+
+fn check(name: &str, size: &str, args: &[&str]) {
+    let output = render(name, size, args);
+    let reference = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(format!("{name}.png"));
+
+    if env::var_os("LSOD_BLESS").is_some() {
+        fs::copy(&output, &reference).unwrap();
+        eprintln!("recorded reference image {}", reference.display());
+        return;
+    }
+
+    assert!(
+        reference.exists(),
+        "missing reference image {}, record it with LSOD_BLESS=1",
+        reference.display()
+    );
+
+    let expected = load(&reference);
+    let actual = load(&output);
+
+    assert_eq!(
+        (actual.width, actual.height),
+        (expected.width, expected.height),
+        "{name} has the wrong size"
+    );
+
+    let different_pixels = actual
+        .data
+        .chunks_exact(4)
+        .zip(expected.data.chunks_exact(4))
+        .filter(|(actual, expected)| {
+            actual
+                .iter()
+                .zip(expected.iter())
+                .any(|(a, e)| a.abs_diff(*e) > CHANNEL_TOLERANCE)
+        })
+        .count();
+
+    let total_pixels = (actual.width * actual.height) as f64;
+
+    assert!(
+        different_pixels as f64 / total_pixels <= MAX_DIFFERENT_PIXELS,
+        "{name} differs from {} in {different_pixels} pixels, the output is at {}",
+        reference.display(),
+        output.display()
+    );
+}
+
+#[test]
+fn not_running() {
+    check("not_running", "300x400", &["--phase", "not-running"]);
+}
+
+#[test]
+fn not_running_small() {
+    check("not_running_small", "200x150", &["--phase", "not-running"]);
+}
+
+#[test]
+fn running_with_splits() {
+    check(
+        "running_with_splits",
+        "300x400",
+        &["--phase", "running", "--split", "2"],
+    );
+}
+
+#[test]
+fn paused() {
+    check("paused", "300x400", &["--phase", "paused", "--split", "1"]);
+}
+
+#[test]
+fn ended_with_pb() {
+    check("ended_with_pb", "300x400", &["--phase", "ended"]);
+}
@@ -0,0 +1,140 @@
+//! The commands `lsod-ctl` sends over the unix socket, both on their own and over the socket.
+#![cfg(unix)]
+
+use std::{
+    fs::{self, Permissions},
+    io::{BufRead, BufReader, ErrorKind, Write},
+    os::unix::{fs::PermissionsExt, net::UnixStream},
+    path::Path,
+    thread,
+    time::{Duration, Instant},
+};
+
+use livesplit_core::{TimeSpan, TimerPhase};
+use lsod_egui::{start_ipc, EventQueue, IpcCommand, ResetPolicy, UserEvent};
+
+mod common;
+
+#[test]
+fn commands_are_parsed() {
+    assert!(matches!(IpcCommand::parse("start"), Ok(IpcCommand::Start)));
+    assert!(matches!(
+        IpcCommand::parse("split\n"),
+        Ok(IpcCommand::Split)
+    ));
+    assert!(matches!(
+        IpcCommand::parse("  undo  "),
+        Ok(IpcCommand::Undo)
+    ));
+    assert!(matches!(IpcCommand::parse("skip"), Ok(IpcCommand::Skip)));
+    assert!(matches!(IpcCommand::parse("pause"), Ok(IpcCommand::Pause)));
+    assert!(matches!(IpcCommand::parse("reset"), Ok(IpcCommand::Reset)));
+    assert!(matches!(
+        IpcCommand::parse("time"),
+        Ok(IpcCommand::CurrentTime)
+    ));
+}
+
+#[test]
+fn comparisons_can_be_switched_or_named() {
+    assert!(matches!(
+        IpcCommand::parse("comparison next"),
+        Ok(IpcCommand::NextComparison)
+    ));
+    assert!(matches!(
+        IpcCommand::parse("comparison previous"),
+        Ok(IpcCommand::PreviousComparison)
+    ));
+    assert!(matches!(
+        IpcCommand::parse("comparison  Best Segments \n"),
+        Ok(IpcCommand::SetComparison(name)) if name == "Best Segments"
+    ));
+}
+
+#[test]
+fn bad_commands_say_what_is_wrong() {
+    assert_eq!(
+        IpcCommand::parse("").err().as_deref(),
+        Some("no command given")
+    );
+    assert_eq!(
+        IpcCommand::parse("comparison").err().as_deref(),
+        Some("comparison needs next, previous or a name")
+    );
+    assert_eq!(
+        IpcCommand::parse("jump").err().as_deref(),
+        Some("unknown command jump")
+    );
+}
+
+#[test]
+fn lsod_ctl_controls_the_timer_over_the_socket() {
+    let path = common::splits_file("lsod_ctl_controls_the_timer_over_the_socket");
+
+    let mut config = common::config(&path);
+    config.reset_policy = ResetPolicy::NeverUpdate;
+
+    let events = EventQueue::default();
+    let mut shared_state = common::shared_state(config, &events);
+    let history_len = shared_state.timer().read().run().attempt_history().len();
+
+    let socket = Path::new(env!("CARGO_TARGET_TMPDIR"))
+        .join("lsod_ctl_controls_the_timer_over_the_socket")
+        .join("lsod.sock");
+    start_ipc(&socket, Box::new(events.clone())).unwrap();
+
+    // only the user can connect
+    let mode = fs::metadata(&socket).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o600);
+
+    let client = thread::spawn(move || {
+        let mut stream = UnixStream::connect(socket).unwrap();
+        stream
+            .write_all(b"start\nsplit\ntime\nreset\njump\n")
+            .unwrap();
+
+        BufReader::new(stream)
+            .lines()
+            .take(5)
+            .map(Result::unwrap)
+            .collect::<Vec<_>>()
+    });
+
+    // what the event loop does
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while !client.is_finished() {
+        assert!(
+            Instant::now() < deadline,
+            "the client didn't get its responses"
+        );
+
+        for event in events.take() {
+            if let UserEvent::IpcCommand(command, sender) = event {
+                sender.send(shared_state.run_ipc_command(command)).unwrap();
+            }
+        }
+
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    let responses = client.join().unwrap();
+    assert_eq!(responses[..2], ["ok", "ok"]);
+    assert!(responses[2].parse::<TimeSpan>().is_ok(), "{}", responses[2]);
+    assert_eq!(responses[3], "ok");
+    assert_eq!(responses[4], "error: unknown command jump");
+
+    // the reset went through the shared state, so the reset policy kept it out of the splits
+    let timer = shared_state.timer().read();
+    assert_eq!(timer.current_phase(), TimerPhase::NotRunning);
+    assert_eq!(timer.run().attempt_history().len(), history_len);
+}
+
+#[test]
+fn sockets_in_a_directory_others_can_enter_are_refused() {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("shared_socket_directory");
+    fs::create_dir_all(&dir).unwrap();
+    fs::set_permissions(&dir, Permissions::from_mode(0o755)).unwrap();
+
+    let error = start_ipc(&dir.join("lsod.sock"), Box::new(EventQueue::default())).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::PermissionDenied);
+}
@@ -0,0 +1,211 @@
+//! LiveSplit's server protocol, both the commands on their own and over a connection.
+
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::TcpStream,
+    thread,
+    time::{Duration, Instant},
+};
+
+use livesplit_core::{
+    timing::formatter::{Regular, TimeFormatter},
+    TimeSpan, TimerPhase, TimingMethod,
+};
+use lsod_egui::{Command, EventQueue, ResetPolicy, SharedState, UserEvent};
+
+mod common;
+
+fn shared_state(name: &str) -> SharedState {
+    let path = common::splits_file(name);
+    common::shared_state(common::config(&path), &EventQueue::default())
+}
+
+fn execute(shared_state: &mut SharedState, line: &str) -> Option<String> {
+    let command = Command::parse(line).unwrap_or_else(|| panic!("{line:?} isn't a command"));
+    shared_state.run_command(command)
+}
+
+#[test]
+fn commands_are_parsed_with_their_arguments() {
+    assert!(matches!(Command::parse("split\r\n"), Some(Command::Split)));
+    assert!(matches!(
+        Command::parse("getcurrenttime"),
+        Some(Command::GetCurrentTime)
+    ));
+    assert!(matches!(
+        Command::parse("switchto gametime"),
+        Some(Command::SwitchTo(TimingMethod::GameTime))
+    ));
+    assert!(matches!(
+        Command::parse("setcomparison Best Segments\n"),
+        Some(Command::SetComparison(comparison)) if comparison == "Best Segments"
+    ));
+    assert!(matches!(
+        Command::parse("getdelta"),
+        Some(Command::GetDelta(None))
+    ));
+
+    match Command::parse("setgametime 1:05.5") {
+        Some(Command::SetGameTime(time)) => assert_eq!(time, TimeSpan::from_seconds(65.5)),
+        _ => panic!("setgametime wasn't parsed"),
+    }
+}
+
+#[test]
+fn unknown_commands_and_bad_arguments_are_ignored() {
+    assert!(Command::parse("").is_none());
+    assert!(Command::parse("jump").is_none());
+    assert!(Command::parse("setgametime").is_none());
+    assert!(Command::parse("setgametime soon").is_none());
+    assert!(Command::parse("switchto sideways").is_none());
+    assert!(Command::parse("setcomparison").is_none());
+}
+
+#[test]
+fn commands_control_the_timer() {
+    let mut shared_state = shared_state("commands_control_the_timer");
+
+    assert_eq!(execute(&mut shared_state, "starttimer"), None);
+    assert_eq!(
+        shared_state.timer().read().current_phase(),
+        TimerPhase::Running
+    );
+
+    assert_eq!(execute(&mut shared_state, "split"), None);
+    assert_eq!(shared_state.timer().read().current_split_index(), Some(1));
+
+    assert_eq!(execute(&mut shared_state, "pause"), None);
+    assert_eq!(
+        shared_state.timer().read().current_phase(),
+        TimerPhase::Paused
+    );
+
+    assert_eq!(execute(&mut shared_state, "unsplit"), None);
+    assert_eq!(shared_state.timer().read().current_split_index(), Some(0));
+
+    assert_eq!(execute(&mut shared_state, "reset"), None);
+    assert_eq!(
+        shared_state.timer().read().current_phase(),
+        TimerPhase::NotRunning
+    );
+
+    // resets go through the shared state, so practice attempts stay out of the splits
+    shared_state.toggle_practice_mode().unwrap();
+    let history_len = shared_state.timer().read().run().attempt_history().len();
+
+    execute(&mut shared_state, "starttimer");
+    execute(&mut shared_state, "split");
+    execute(&mut shared_state, "reset");
+
+    assert_eq!(
+        shared_state.timer().read().run().attempt_history().len(),
+        history_len
+    );
+}
+
+#[test]
+fn queries_describe_the_timer() {
+    let mut shared_state = shared_state("queries_describe_the_timer");
+
+    assert_eq!(execute(&mut shared_state, "ping").as_deref(), Some("pong"));
+    assert_eq!(
+        execute(&mut shared_state, "getsplitindex").as_deref(),
+        Some("-1")
+    );
+    assert_eq!(
+        execute(&mut shared_state, "getcurrenttimerphase").as_deref(),
+        Some("NotRunning")
+    );
+    assert_eq!(
+        execute(&mut shared_state, "getcurrentsplitname").as_deref(),
+        Some("-")
+    );
+
+    // the personal best, there's no attempt yet
+    let final_time = Regular::new()
+        .format(Some(TimeSpan::from_seconds(240.)))
+        .to_string();
+    assert_eq!(
+        execute(&mut shared_state, "getfinaltime").as_deref(),
+        Some(final_time.as_str())
+    );
+
+    execute(&mut shared_state, "starttimer");
+    execute(&mut shared_state, "split");
+
+    assert_eq!(
+        execute(&mut shared_state, "getsplitindex").as_deref(),
+        Some("1")
+    );
+    assert_eq!(
+        execute(&mut shared_state, "getcurrentsplitname").as_deref(),
+        Some("Second")
+    );
+    assert_eq!(
+        execute(&mut shared_state, "getprevioussplitname").as_deref(),
+        Some("First")
+    );
+    assert_eq!(
+        execute(&mut shared_state, "getcurrenttimerphase").as_deref(),
+        Some("Running")
+    );
+}
+
+#[test]
+fn clients_control_the_timer_over_a_connection() {
+    let path = common::splits_file("clients_control_the_timer_over_a_connection");
+
+    let mut config = common::config(&path);
+    config.reset_policy = ResetPolicy::NeverUpdate;
+    config.server.enabled = true;
+    // any free port
+    config.server.port = 0;
+
+    let events = EventQueue::default();
+    let mut shared_state = common::shared_state(config, &events);
+    let address = shared_state.server().unwrap().address();
+    let history_len = shared_state.timer().read().run().attempt_history().len();
+
+    let client = thread::spawn(move || {
+        let mut stream = TcpStream::connect(address).unwrap();
+        // only queries get a response, the commands before them have been run by then
+        stream
+            .write_all(
+                b"starttimer\nsplit\ngetsplitindex\ngetcurrenttime\nreset\ngetcurrenttimerphase\n",
+            )
+            .unwrap();
+
+        BufReader::new(stream)
+            .lines()
+            .take(3)
+            .map(Result::unwrap)
+            .collect::<Vec<_>>()
+    });
+
+    // what the event loop does
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while !client.is_finished() {
+        assert!(
+            Instant::now() < deadline,
+            "the client didn't get its responses"
+        );
+
+        for event in events.take() {
+            if let UserEvent::ServerCommand(command, sender) = event {
+                sender.send(shared_state.run_command(command)).unwrap();
+            }
+        }
+
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    let responses = client.join().unwrap();
+    assert_eq!(responses[0], "1");
+    assert!(responses[1].parse::<TimeSpan>().is_ok(), "{}", responses[1]);
+    assert_eq!(responses[2], "NotRunning");
+
+    // the reset went through the shared state, so the reset policy kept it out of the splits
+    let timer = shared_state.timer().read();
+    assert_eq!(timer.current_phase(), TimerPhase::NotRunning);
+    assert_eq!(timer.run().attempt_history().len(), history_len);
+}
@@ -0,0 +1,552 @@
+//! The state the windows share, built without an event loop or keyboard hook.
+
+use std::{
+    fs::{self, File},
+    io::BufReader,
+    path::Path,
+};
+
+use livesplit_core::{hotkey::KeyCode, run::parser::composite, TimeSpan, TimerPhase, TimingMethod};
+use lsod_egui::{
+    configuration::Profile, EventQueue, Hotkey, HotkeyBackend, MemoryHotkeys, ResetPolicy, RunEdit,
+    SharedState, UserEvent,
+};
+
+mod common;
+
+#[test]
+fn starts_with_the_configured_splits_and_hotkeys() {
+    let path = common::splits_file("starts_with_the_configured_splits_and_hotkeys");
+
+    let mut config = common::config(&path);
+    config.profile_mut().hotkey_config.split = Some(KeyCode::Numpad1);
+
+    let events = EventQueue::default();
+    let shared_state = common::shared_state(config, &events);
+
+    let timer = shared_state.timer().read();
+    assert_eq!(timer.run().game_name(), "Golden Image Test");
+    assert_eq!(timer.run().len(), 3);
+    assert_eq!(timer.current_phase(), TimerPhase::NotRunning);
+
+    assert_eq!(
+        shared_state.hotkeys().config().split,
+        Some(KeyCode::Numpad1)
+    );
+    assert!(events.take().is_empty());
+}
+
+#[test]
+fn missing_splits_fall_back_to_an_empty_run() {
+    let path = Path::new(env!("CARGO_TARGET_TMPDIR")).join("does_not_exist.lss");
+
+    let shared_state = common::shared_state(common::config(&path), &EventQueue::default());
+
+    let timer = shared_state.timer().read();
+    assert_eq!(timer.run().game_name(), "untitled");
+    assert_eq!(timer.run().len(), 1);
+}
+
+#[test]
+fn timer_can_be_controlled_without_a_display() {
+    let path = common::splits_file("timer_can_be_controlled_without_a_display");
+
+    let mut config = common::config(&path);
+    config.reset_policy = ResetPolicy::NeverUpdate;
+    config.profile_mut().hotkey_config.split = Some(KeyCode::F13);
+    config.profile_mut().hotkey_config.reset = Some(KeyCode::F14);
+
+    let events = EventQueue::default();
+    let mut shared_state = common::shared_state(config, &events);
+    let history_len = shared_state.timer().read().run().attempt_history().len();
+
+    // what the event loop does with the hotkeys that need the shared state
+    let run_events = |shared_state: &mut SharedState| {
+        for event in events.take() {
+            if let UserEvent::Hotkey(hotkey) = event {
+                shared_state.run_hotkey(hotkey);
+            }
+        }
+    };
+
+    shared_state.hotkeys().press(KeyCode::F13);
+    shared_state.hotkeys().press(KeyCode::F13);
+    run_events(&mut shared_state);
+    assert_eq!(shared_state.timer().read().current_split_index(), Some(1));
+
+    // resets go through the event loop, so the timer is still running until it gets to them
+    shared_state.hotkeys().press(KeyCode::F14);
+    assert_eq!(
+        shared_state.timer().read().current_phase(),
+        TimerPhase::Running
+    );
+
+    run_events(&mut shared_state);
+    let timer = shared_state.timer().read();
+    assert_eq!(timer.current_phase(), TimerPhase::NotRunning);
+    assert_eq!(timer.run().attempt_history().len(), history_len);
+}
+
+#[test]
+fn switching_profiles_loads_their_settings() {
+    let path = common::splits_file("switching_profiles_loads_their_settings");
+
+    let mut config = common::config(&path);
+    config.profiles.push(Profile {
+        name: "Practice".to_owned(),
+        splits_path: None,
+        size: (200, 500),
+        ..Default::default()
+    });
+    config.profiles[1].hotkey_config.split = Some(KeyCode::Numpad7);
+
+    let events = EventQueue::default();
+    let mut shared_state = common::shared_state(config, &events);
+
+    shared_state.switch_profile(1).unwrap();
+
+    assert_eq!(shared_state.config().current_profile, 1);
+    assert_eq!(shared_state.config().profile().size, (200, 500));
+    assert_eq!(shared_state.timer().read().run().game_name(), "untitled");
+    assert_eq!(
+        shared_state.hotkeys().config().split,
+        Some(KeyCode::Numpad7)
+    );
+    assert!(events
+        .take()
+        .iter()
+        .any(|event| matches!(event, UserEvent::Resize)));
+
+    shared_state.switch_profile(0).unwrap();
+
+    assert_eq!(
+        shared_state.timer().read().run().game_name(),
+        "Golden Image Test"
+    );
+}
+
+#[test]
+fn profiles_cant_be_switched_while_the_timer_is_running() {
+    let path = common::splits_file("profiles_cant_be_switched_while_the_timer_is_running");
+
+    let mut config = common::config(&path);
+    config.profiles.push(Profile::default());
+
+    let mut shared_state = common::shared_state(config, &EventQueue::default());
+    shared_state.timer().write().start();
+
+    assert!(shared_state.switch_profile(1).is_err());
+    assert_eq!(shared_state.config().current_profile, 0);
+}
+
+#[test]
+fn practice_attempts_are_not_recorded() {
+    let path = common::splits_file("practice_attempts_are_not_recorded");
+    let mut shared_state = common::shared_state(common::config(&path), &EventQueue::default());
+
+    // every segment's gold and personal best split time
+    let times = |shared_state: &SharedState| {
+        shared_state
+            .timer()
+            .read()
+            .run()
+            .segments()
+            .iter()
+            .map(|segment| {
+                (
+                    segment.best_segment_time()[TimingMethod::RealTime],
+                    segment.personal_best_split_time()[TimingMethod::RealTime],
+                )
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let (attempt_count, history_len) = {
+        let timer = shared_state.timer().read();
+        (
+            timer.run().attempt_count(),
+            timer.run().attempt_history().len(),
+        )
+    };
+    let times_before = times(&shared_state);
+    let file_before = fs::read_to_string(&path).unwrap();
+
+    shared_state.run_hotkey(Hotkey::TogglePracticeMode);
+    assert!(shared_state.practice_mode());
+
+    // splits right after each other are golds and a personal best, if they were recorded
+    let segments = shared_state.timer().read().run().len();
+    for _ in 0..=segments {
+        shared_state.run_hotkey(Hotkey::Split);
+    }
+    assert_eq!(
+        shared_state.timer().read().current_phase(),
+        TimerPhase::Ended
+    );
+
+    shared_state.check_for_end();
+    shared_state.run_hotkey(Hotkey::Reset);
+
+    {
+        let timer = shared_state.timer().read();
+        assert_eq!(timer.current_phase(), TimerPhase::NotRunning);
+        assert_eq!(timer.run().attempt_count(), attempt_count);
+        assert_eq!(timer.run().attempt_history().len(), history_len);
+    }
+
+    assert_eq!(times(&shared_state), times_before);
+    assert_eq!(fs::read_to_string(&path).unwrap(), file_before);
+}
+
+#[test]
+fn practice_mode_cant_be_toggled_during_an_attempt() {
+    let path = common::splits_file("practice_mode_cant_be_toggled_during_an_attempt");
+    let mut shared_state = common::shared_state(common::config(&path), &EventQueue::default());
+
+    shared_state.run_hotkey(Hotkey::Split);
+
+    assert!(shared_state.toggle_practice_mode().is_err());
+    assert!(!shared_state.practice_mode());
+}
+
+#[test]
+fn starting_from_a_segment_uses_the_personal_best_times() {
+    let path = common::splits_file("starting_from_a_segment_uses_the_personal_best_times");
+    let mut shared_state = common::shared_state(common::config(&path), &EventQueue::default());
+
+    let attempt_count = shared_state.timer().read().run().attempt_count();
+
+    shared_state.start_from_segment(2).unwrap();
+    assert!(shared_state.practice_mode());
+
+    {
+        let timer = shared_state.timer().read();
+        assert_eq!(timer.current_split_index(), Some(2));
+
+        // the second split of the pb was at 2:40
+        let time = timer.snapshot().current_time()[TimingMethod::RealTime].unwrap();
+        assert!(time >= TimeSpan::from_seconds(160.));
+    }
+
+    shared_state.reset();
+
+    assert!(!shared_state.practice_mode());
+
+    {
+        let timer = shared_state.timer().read();
+        assert_eq!(timer.run().offset(), TimeSpan::zero());
+        assert_eq!(timer.run().attempt_count(), attempt_count);
+    }
+
+    // practice mode that was on before stays on
+    shared_state.toggle_practice_mode().unwrap();
+    shared_state.start_from_segment(1).unwrap();
+    shared_state.reset();
+    assert!(shared_state.practice_mode());
+}
+
+#[test]
+fn the_reset_policy_decides_whether_attempts_are_recorded() {
+    let path = common::splits_file("the_reset_policy_decides_whether_attempts_are_recorded");
+
+    let mut config = common::config(&path);
+    config.reset_policy = ResetPolicy::NeverUpdate;
+
+    let mut shared_state = common::shared_state(config, &EventQueue::default());
+    let history_len = shared_state.timer().read().run().attempt_history().len();
+
+    shared_state.run_hotkey(Hotkey::Split);
+    shared_state.run_hotkey(Hotkey::Reset);
+    assert_eq!(
+        shared_state.timer().read().run().attempt_history().len(),
+        history_len
+    );
+
+    shared_state.run_hotkey(Hotkey::Split);
+    shared_state.run_hotkey(Hotkey::ResetAndDiscard);
+    assert_eq!(
+        shared_state.timer().read().run().attempt_history().len(),
+        history_len
+    );
+}
+
+#[test]
+fn resetting_can_always_update_the_splits() {
+    let path = common::splits_file("resetting_can_always_update_the_splits");
+
+    let mut config = common::config(&path);
+    config.reset_policy = ResetPolicy::AlwaysUpdate;
+
+    let mut shared_state = common::shared_state(config, &EventQueue::default());
+    let history_len = shared_state.timer().read().run().attempt_history().len();
+
+    shared_state.run_hotkey(Hotkey::Split);
+    shared_state.run_hotkey(Hotkey::Reset);
+
+    assert_eq!(
+        shared_state.timer().read().run().attempt_history().len(),
+        history_len + 1
+    );
+}
+
+#[test]
+fn a_new_pb_is_saved_when_the_timer_reaches_the_end() {
+    let path = common::splits_file("a_new_pb_is_saved_when_the_timer_reaches_the_end");
+
+    let mut config = common::config(&path);
+    config.reset_policy = ResetPolicy::AlwaysUpdate;
+
+    let mut shared_state = common::shared_state(config, &EventQueue::default());
+
+    // starting counts as a split too
+    let segments = shared_state.timer().read().run().len();
+    for _ in 0..=segments {
+        shared_state.run_hotkey(Hotkey::Split);
+    }
+    shared_state.check_for_end();
+
+    // the final times stay up until the reset
+    assert_eq!(
+        shared_state.timer().read().current_phase(),
+        TimerPhase::Ended
+    );
+
+    let saved = composite::parse(BufReader::new(File::open(&path).unwrap()), None, true)
+        .unwrap()
+        .run;
+    let pb = saved.segments()[2].personal_best_split_time()[TimingMethod::RealTime].unwrap();
+    assert!(pb < TimeSpan::from_seconds(60.));
+
+    shared_state.reset();
+
+    let timer = shared_state.timer().read();
+    assert_eq!(timer.current_phase(), TimerPhase::NotRunning);
+    assert_eq!(
+        timer.run().attempt_history().len(),
+        saved.attempt_history().len()
+    );
+    assert!(!timer.run().has_been_modified());
+}
+
+#[test]
+fn edits_during_an_attempt_are_made_on_reset() {
+    let path = common::splits_file("edits_during_an_attempt_are_made_on_reset");
+    let mut shared_state = common::shared_state(common::config(&path), &EventQueue::default());
+
+    shared_state.run_hotkey(Hotkey::Split);
+    shared_state.queue_edits([
+        RunEdit::SegmentName(0, "Renamed".to_owned()),
+        RunEdit::BestSegmentTime(0, TimingMethod::RealTime, "0:50".to_owned()),
+    ]);
+
+    assert_eq!(shared_state.timer().read().run().segment(0).name(), "First");
+
+    shared_state.reset_and_discard();
+
+    let timer = shared_state.timer().read();
+    let segment = timer.run().segment(0);
+    assert_eq!(segment.name(), "Renamed");
+    assert_eq!(
+        segment.best_segment_time()[TimingMethod::RealTime],
+        Some(TimeSpan::from_seconds(50.))
+    );
+}
+
+#[test]
+fn edits_during_an_attempt_are_saved_with_it() {
+    let path = common::splits_file("edits_during_an_attempt_are_saved_with_it");
+
+    let mut config = common::config(&path);
+    config.reset_policy = ResetPolicy::AlwaysUpdate;
+    let mut shared_state = common::shared_state(config, &EventQueue::default());
+
+    shared_state.run_hotkey(Hotkey::Split);
+    // game time isn't running, a real time gold would be beaten by the attempt's own segment
+    shared_state.queue_edits([RunEdit::BestSegmentTime(
+        2,
+        TimingMethod::GameTime,
+        "0:30".to_owned(),
+    )]);
+
+    let segments = shared_state.timer().read().run().len();
+    for _ in 0..segments {
+        shared_state.run_hotkey(Hotkey::Split);
+    }
+    shared_state.check_for_end();
+
+    let saved = composite::parse(BufReader::new(File::open(&path).unwrap()), None, true)
+        .unwrap()
+        .run;
+    assert_eq!(
+        saved.segment(2).best_segment_time()[TimingMethod::GameTime],
+        Some(TimeSpan::from_seconds(30.))
+    );
+
+    shared_state.reset();
+
+    let timer = shared_state.timer().read();
+    assert_eq!(
+        timer.run().segment(2).best_segment_time()[TimingMethod::GameTime],
+        Some(TimeSpan::from_seconds(30.))
+    );
+    assert!(!timer.run().has_been_modified());
+}
+
+#[test]
+fn cosmetic_edits_during_an_attempt_are_saved_right_away() {
+    let path = common::splits_file("cosmetic_edits_during_an_attempt_are_saved_right_away");
+    let mut shared_state = common::shared_state(common::config(&path), &EventQueue::default());
+
+    shared_state.run_hotkey(Hotkey::Split);
+    shared_state.queue_edits([
+        RunEdit::GameName("Renamed Game".to_owned()),
+        RunEdit::SegmentName(1, "Renamed".to_owned()),
+        RunEdit::BestSegmentTime(1, TimingMethod::RealTime, "0:50".to_owned()),
+    ]);
+    shared_state.save_cosmetic_edits().unwrap();
+
+    let saved = composite::parse(BufReader::new(File::open(&path).unwrap()), None, true)
+        .unwrap()
+        .run;
+    assert_eq!(saved.game_name(), "Renamed Game");
+    assert_eq!(saved.segment(1).name(), "Renamed");
+    assert_eq!(
+        saved.segment(1).best_segment_time()[TimingMethod::RealTime],
+        Some(TimeSpan::from_seconds(90.))
+    );
+
+    // the attempt goes on
+    assert_eq!(
+        shared_state.timer().read().current_phase(),
+        TimerPhase::Running
+    );
+}
+
+#[test]
+fn splits_are_announced() {
+    let path = common::splits_file("splits_are_announced");
+    let mut shared_state = common::shared_state(common::config(&path), &EventQueue::default());
+
+    shared_state.check_for_announcements();
+    assert_eq!(shared_state.last_announcement(), None);
+
+    shared_state.run_hotkey(Hotkey::Split);
+    shared_state.check_for_announcements();
+    assert_eq!(shared_state.last_announcement(), Some("Started"));
+
+    shared_state.run_hotkey(Hotkey::Split);
+    shared_state.check_for_announcements();
+    assert_eq!(
+        shared_state.last_announcement(),
+        Some("First, 65.0 seconds ahead, gold")
+    );
+
+    let description = shared_state.describe_timer();
+    assert!(description.starts_with("Golden Image Test - Any%, Second, "));
+    assert!(description.ends_with(", last split 65.0 seconds ahead"));
+
+    shared_state.run_hotkey(Hotkey::Skip);
+    shared_state.check_for_announcements();
+    assert_eq!(shared_state.last_announcement(), Some("Skipped Second"));
+
+    shared_state.run_hotkey(Hotkey::Split);
+    shared_state.check_for_announcements();
+    assert!(shared_state
+        .last_announcement()
+        .unwrap()
+        .ends_with(", new personal best"));
+
+    shared_state.reset_and_discard();
+    shared_state.check_for_announcements();
+    assert_eq!(shared_state.last_announcement(), Some("Reset"));
+    assert_eq!(
+        shared_state.describe_timer(),
+        "Golden Image Test - Any%, not running"
+    );
+}
+
+#[test]
+fn the_zoom_scales_the_window_size() {
+    let profile = Profile {
+        size: (320, 240),
+        zoom: 1.5,
+        ..Profile::default()
+    };
+
+    let size = profile.window_size();
+    assert_eq!((size.width, size.height), (480., 360.));
+}
+
+#[test]
+fn hotkeys_on_the_same_key_change_nothing() {
+    let path = common::splits_file("hotkeys_on_the_same_key_change_nothing");
+    let shared_state = common::shared_state(common::config(&path), &EventQueue::default());
+
+    let config = shared_state.hotkeys().config();
+    let extra_config = shared_state.hotkeys().extra_config();
+
+    let mut hotkeys = MemoryHotkeys::with_config(
+        shared_state.timer().clone(),
+        Box::new(EventQueue::default()),
+        config,
+        extra_config,
+    )
+    .unwrap();
+
+    let mut new_config = config;
+    new_config.split = Some(KeyCode::F13);
+    let mut new_extra_config = extra_config;
+    new_extra_config.toggle_practice_mode = Some(KeyCode::F13);
+
+    assert!(hotkeys.set_config(new_config, new_extra_config).is_err());
+    assert_eq!(hotkeys.config().split, config.split);
+    assert_eq!(hotkeys.extra_config().toggle_practice_mode, None);
+
+    new_extra_config.toggle_practice_mode = Some(KeyCode::F14);
+
+    hotkeys.set_config(new_config, new_extra_config).unwrap();
+    assert_eq!(hotkeys.config().split, Some(KeyCode::F13));
+    assert_eq!(
+        hotkeys.extra_config().toggle_practice_mode,
+        Some(KeyCode::F14)
+    );
+}
+
+#[test]
+fn auto_splitter_resets_follow_practice_mode() {
+    let path = common::splits_file("auto_splitter_resets_follow_practice_mode");
+    let mut shared_state = common::shared_state(common::config(&path), &EventQueue::default());
+
+    shared_state.toggle_practice_mode().unwrap();
+    let attempt_count = shared_state.timer().read().run().attempt_count();
+
+    // what the runtime would do while running a script
+    {
+        let mut timer = shared_state.auto_splitter_timer().write();
+        timer.start();
+        timer.split();
+    }
+    shared_state.update_auto_splitter();
+
+    assert_eq!(shared_state.timer().read().current_split_index(), Some(1));
+
+    // the user splits too, the auto splitter's timer has to follow
+    shared_state.run_hotkey(Hotkey::Split);
+    shared_state.update_auto_splitter();
+
+    assert_eq!(
+        shared_state
+            .auto_splitter_timer()
+            .read()
+            .current_split_index(),
+        Some(2)
+    );
+
+    shared_state.auto_splitter_timer().write().reset(true);
+    shared_state.update_auto_splitter();
+
+    let timer = shared_state.timer().read();
+    assert_eq!(timer.current_phase(), TimerPhase::NotRunning);
+    assert_eq!(timer.run().attempt_count(), attempt_count);
+    assert!(timer.run().attempt_history().is_empty());
+}